@@ -40,6 +40,16 @@ fn day_18_part_2(i: &day_18::Input) -> usize {
     day_18::biggest_pair_sum(i).unwrap_or(0)
 }
 
+fn day_18_part_2_flat(i: &day_18::Input) -> usize {
+    day_18::biggest_pair_sum_as::<day_18::FlatNumber>(i).unwrap_or(0)
+}
+
+fn day_18_part_2_parallel(i: &day_18::Input, threads: usize) -> usize {
+    day_18::biggest_pair_sum_parallel::<day_18::FlatNumber>(i, threads)
+        .map(|b| b.magnitude)
+        .unwrap_or(0)
+}
+
 fn day_19_map_building(i: &day_19::Input) -> usize {
     i.build_map().map(|m| m.scanners.len()).unwrap_or(0)
 }
@@ -101,6 +111,15 @@ fn criterion_benchmark(c: &mut Criterion) {
         b.iter(|| day_18_part_2(black_box(&day_18_input)))
     });
 
+    c.bench_function("Day 18 Part 2 (flat)", |b| {
+        b.iter(|| day_18_part_2_flat(black_box(&day_18_input)))
+    });
+
+    let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+    c.bench_function("Day 18 Part 2 (flat, parallel)", |b| {
+        b.iter(|| day_18_part_2_parallel(black_box(&day_18_input), threads))
+    });
+
     c.bench_function("Day 19 Part 2", |b| {
         b.iter(|| day_19_map_building(black_box(&day_19_input)))
     });
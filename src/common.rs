@@ -1,8 +1,88 @@
 use std::num::ParseIntError;
+use std::result::Result as StdResult;
 
+use combine::easy;
 use combine::parser::char::*;
+use combine::stream::position::{self, Positioner, SourcePosition};
 use combine::*;
 
+use crate::error;
+
+pub(crate) type PositionedStream<'a> = easy::Stream<position::Stream<&'a str, SourcePosition>>;
+
+/// Runs the parser over all of `s`, keeping track of lines and columns so that failures, including
+/// any unparsed trailing input, say where they happened.
+pub(crate) fn parse_all<'a, P>(parser: P, s: &'a str) -> StdResult<P::Output, error::ParseError>
+where
+    P: Parser<PositionedStream<'a>>,
+{
+    parse_from_line(parser, s, 1)
+}
+
+/// As [parse_all], for when `s` is a section of some bigger input, starting at `first_line`
+pub(crate) fn parse_from_line<'a, P>(
+    mut parser: P,
+    s: &'a str,
+    first_line: usize,
+) -> StdResult<P::Output, error::ParseError>
+where
+    P: Parser<PositionedStream<'a>>,
+{
+    let stream = easy::Stream(position::Stream::with_positioner(
+        s,
+        SourcePosition {
+            line: first_line as i32,
+            column: 1,
+        },
+    ));
+    let (output, rest) = parser
+        .parse(stream)
+        .map_err(|e| error::ParseError::from_errors(s, first_line, e))?;
+    let position::Stream {
+        input: remaining,
+        mut positioner,
+    } = rest.0;
+    let trailing = remaining.trim_start();
+    if let Some(c) = trailing.chars().next() {
+        remaining[..remaining.len() - trailing.len()]
+            .chars()
+            .for_each(|skipped| positioner.update(&skipped));
+        return Err(error::ParseError::at(
+            s,
+            first_line,
+            positioner.line as usize,
+            positioner.column as usize,
+            format!("Unexpected `{}`; Expected end of input", c),
+        ));
+    }
+    Ok(output)
+}
+
+/// Splits `s` into sections separated by blank lines, along with the line each section starts on
+pub(crate) fn sections(s: &str) -> Vec<(usize, &str)> {
+    let mut sections = Vec::new();
+    let mut current: Option<(usize, usize, usize)> = None; // (line, start offset, end offset)
+    let mut offset = 0;
+    for (idx, line) in s.split('\n').enumerate() {
+        let line_end = offset + line.len();
+        if line.trim().is_empty() {
+            if let Some((section_line, start, end)) = current.take() {
+                sections.push((section_line, &s[start..end]));
+            }
+        } else {
+            current = match current {
+                Some((section_line, start, _)) => Some((section_line, start, line_end)),
+                None => Some((idx + 1, offset, line_end)),
+            };
+        }
+        offset = line_end + 1;
+    }
+    if let Some((section_line, start, end)) = current {
+        sections.push((section_line, &s[start..end]));
+    }
+    sections
+}
+
 pub(crate) fn usize_parser<Input>() -> impl Parser<Input, Output = usize>
 where
     Input: Stream<Token = char>,
@@ -61,6 +141,43 @@ where
 mod tests {
     use super::*;
 
+    #[test]
+    fn parse_all_test() {
+        let r: Vec<usize> = parse_all(sep_by1(usize_parser(), char(',')), "1,2,3\n").unwrap();
+        assert_eq!(vec![1, 2, 3], r);
+    }
+
+    #[test]
+    fn parse_all_error_position_test() {
+        let r: StdResult<Vec<usize>, _> = parse_all(sep_by1(usize_parser(), char(',')), "1,2,\n3");
+        let e = r.unwrap_err();
+        assert_eq!((1, 5), (e.line, e.column));
+    }
+
+    #[test]
+    fn parse_all_trailing_input_test() {
+        let r: StdResult<Vec<usize>, _> =
+            parse_all(sep_by1(usize_parser(), char(',')), "1,2\n\n  x");
+        let e = r.unwrap_err();
+        assert_eq!((3, 3), (e.line, e.column));
+        assert_eq!("Unexpected `x`; Expected end of input", e.message);
+    }
+
+    #[test]
+    fn parse_from_line_test() {
+        let r: StdResult<Vec<usize>, _> =
+            parse_from_line(sep_by1(usize_parser(), newline()), "1\nx", 7);
+        let e = r.unwrap_err();
+        assert_eq!((8, 1), (e.line, e.column));
+        assert_eq!("x", e.snippet);
+    }
+
+    #[test]
+    fn sections_test() {
+        let r = sections("\na\nb\n\n\nc\n  \nd\n");
+        assert_eq!(vec![(2, "a\nb"), (6, "c"), (8, "d")], r);
+    }
+
     #[test]
     fn string_to_usize_test() {
         let (r, _) = usize_parser().easy_parse("123").unwrap();
@@ -2,28 +2,54 @@ use anyhow::Result;
 use itertools::*;
 use std::cmp::Ordering;
 use std::cmp::Ordering::*;
+use std::result::Result as StdResult;
 
-const INPUT: &str = include_str!("../data/day_01_input");
-
-pub fn run() -> Result<()> {
-    println!("*** Day 1: Sonar Sweep ***");
-    println!("Input: {}", INPUT);
-    let nums = string_to_digits(INPUT);
-    let changes = to_changes(&nums);
-    let increases = count_increases(changes);
-    println!("Solution 1: {:?}\n", increases);
-    let three_measurement_window_sums = nums
-        .windows(3)
-        .map(|window| window.iter().sum())
-        .collect::<Vec<isize>>();
-    let changes = to_changes(&three_measurement_window_sums);
-    let increases = count_increases(changes);
-    println!("Solution 2: {:?}\n", increases);
-    Ok(())
+use crate::error;
+use crate::solution::Solution;
+
+pub const INPUT: &str = include_str!("../data/day_01_input");
+
+pub struct Day01;
+
+impl Solution for Day01 {
+    const DAY: usize = 1;
+    const TITLE: &'static str = "Sonar Sweep";
+    const INPUT: &'static str = INPUT;
+
+    type Input = Vec<isize>;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(s: &str) -> Result<Self::Input> {
+        Ok(string_to_digits(s).map_err(|e| e.on_day(Self::DAY))?)
+    }
+
+    fn part_1(nums: &Self::Input) -> Result<Self::Part1> {
+        let changes = to_changes(nums);
+        Ok(count_increases(changes))
+    }
+
+    fn part_2(nums: &Self::Input) -> Result<Self::Part2> {
+        let three_measurement_window_sums = nums
+            .windows(3)
+            .map(|window| window.iter().sum())
+            .collect::<Vec<isize>>();
+        let changes = to_changes(&three_measurement_window_sums);
+        Ok(count_increases(changes))
+    }
 }
 
-fn string_to_digits(s: &str) -> Vec<isize> {
-    s.split('\n').filter_map(|v| v.parse().ok()).collect()
+fn string_to_digits(s: &str) -> StdResult<Vec<isize>, error::ParseError> {
+    s.lines()
+        .enumerate()
+        .filter(|(_, l)| !l.trim().is_empty())
+        .map(|(idx, l)| {
+            l.trim().parse().map_err(|e| {
+                let column = l.len() - l.trim_start().len() + 1;
+                error::ParseError::at(s, 1, idx + 1, column, format!("Invalid depth: {}", e))
+            })
+        })
+        .collect()
 }
 
 fn to_changes(v: &[isize]) -> impl Iterator<Item = Ordering> + '_ {
@@ -40,7 +66,9 @@ mod tests {
 
     #[test]
     fn string_to_digits_test() {
-        assert_eq!(string_to_digits("1234\n5432"), vec![1234, 5432]);
+        assert_eq!(string_to_digits("1234\n5432").unwrap(), vec![1234, 5432]);
+        let e = string_to_digits("1234\n 54x2").unwrap_err();
+        assert_eq!((2, 2), (e.line, e.column));
     }
 
     #[test]
@@ -1,30 +1,43 @@
 use std::result::Result as StdResult;
 
 use anyhow::Result;
-use combine::easy;
 use combine::parser::char::*;
 use combine::*;
 
 use Move::*;
 
-use crate::common::usize_parser;
+use crate::common::{parse_all, usize_parser};
+use crate::error;
+use crate::solution::Solution;
 
-const INPUT: &str = include_str!("../data/day_02_input");
+pub const INPUT: &str = include_str!("../data/day_02_input");
 
-pub fn run() -> Result<()> {
-    println!("*** Day 2: Dive! ***");
-    println!("Input: {}", INPUT);
-    let program = parse(INPUT)?;
-    let position_1 = run_prog_1(&program);
-    println!("Solution 1: {:?}\n", position_1.solution());
+pub struct Day02;
 
-    let sub = run_prog_2(&program);
-    println!("Solution 2: {:?}\n", sub.position.solution());
-    Ok(())
+impl Solution for Day02 {
+    const DAY: usize = 2;
+    const TITLE: &'static str = "Dive!";
+    const INPUT: &'static str = INPUT;
+
+    type Input = Program;
+    type Part1 = isize;
+    type Part2 = isize;
+
+    fn parse(s: &str) -> Result<Self::Input> {
+        Ok(parse(s).map_err(|e| e.on_day(Self::DAY))?)
+    }
+
+    fn part_1(program: &Self::Input) -> Result<Self::Part1> {
+        Ok(run_prog_1(program).solution())
+    }
+
+    fn part_2(program: &Self::Input) -> Result<Self::Part2> {
+        Ok(run_prog_2(program).position.solution())
+    }
 }
 
 #[derive(Debug, Eq, PartialEq)]
-struct Program(Vec<Move>);
+pub struct Program(Vec<Move>);
 
 #[derive(Debug, Eq, PartialEq)]
 struct Position {
@@ -51,7 +64,7 @@ enum Move {
     Up(usize),
 }
 
-fn parse(s: &str) -> StdResult<Program, easy::ParseError<&str>> {
+fn parse(s: &str) -> StdResult<Program, error::ParseError> {
     let single_instruction_parser = attempt(
         string("forward")
             .skip(space())
@@ -70,9 +83,8 @@ fn parse(s: &str) -> StdResult<Program, easy::ParseError<&str>> {
                     .map(|(_, num)| Move::Up(num)),
             )),
     );
-    let mut parser = many(single_instruction_parser.skip(spaces())).map(Program);
-    let (r, _) = parser.easy_parse(s)?;
-    Ok(r)
+    let parser = many(single_instruction_parser.skip(spaces())).map(Program);
+    parse_all(parser, s)
 }
 
 fn run_prog_1(p: &Program) -> Position {
@@ -1,23 +1,39 @@
 use std::result::Result as StdResult;
 
 use anyhow::Result;
-use combine::easy;
 use combine::parser::char::*;
 use combine::stream::easy::{Error, Info};
 use combine::*;
 use itertools::{FoldWhile, Itertools};
 
-const INPUT: &str = include_str!("../data/day_03_input");
-
-pub fn run() -> Result<()> {
-    println!("*** Day 3: Binary Diagnostic ***");
-    println!("Input: {}", INPUT);
-    let input = Input::parse(INPUT)?;
-    let p_c = power_consumption(&input);
-    println!("Solution 1: {:?}\n", p_c);
-    let life_support_rating = life_support_rating(&input);
-    println!("Solution 2: {:?}\n", life_support_rating);
-    Ok(())
+use crate::common::parse_all;
+use crate::error;
+use crate::solution::Solution;
+
+pub const INPUT: &str = include_str!("../data/day_03_input");
+
+pub struct Day03;
+
+impl Solution for Day03 {
+    const DAY: usize = 3;
+    const TITLE: &'static str = "Binary Diagnostic";
+    const INPUT: &'static str = INPUT;
+
+    type Input = Input;
+    type Part1 = u64;
+    type Part2 = u64;
+
+    fn parse(s: &str) -> Result<Self::Input> {
+        Ok(Input::parse(s).map_err(|e| e.on_day(Self::DAY))?)
+    }
+
+    fn part_1(input: &Self::Input) -> Result<Self::Part1> {
+        Ok(power_consumption(input).0)
+    }
+
+    fn part_2(input: &Self::Input) -> Result<Self::Part2> {
+        Ok(life_support_rating(input).0)
+    }
 }
 
 #[derive(Debug, Eq, PartialEq)]
@@ -33,16 +49,16 @@ struct CO2ScrubberRating(u64);
 struct LifeSupportRating(u64);
 
 #[derive(Debug, Eq, PartialEq)]
-struct Input {
+pub struct Input {
     vecs: Vec<Vec<bool>>,
     bits: usize,
 }
 
 impl Input {
-    fn parse(s: &str) -> StdResult<Input, easy::ParseError<&str>> {
+    fn parse(s: &str) -> StdResult<Input, error::ParseError> {
         let single = many1(char('0').map(|_| false).or(char('1').map(|_| true)));
-        let mut parser = many1(single.skip(spaces())).and_then(|vecs: Vec<Vec<bool>>| {
-            if let Some(first) = vecs.get(0) {
+        let parser = many1(single.skip(spaces())).and_then(|vecs: Vec<Vec<bool>>| {
+            if let Some(first) = vecs.first() {
                 let first_len = first.len();
                 if vecs.iter().all(|v| v.len() == first_len) {
                     Ok(Input {
@@ -56,8 +72,7 @@ impl Input {
                 Err(Error::Unexpected(Info::Static("No input")))
             }
         });
-        let (r, _) = parser.easy_parse(s)?;
-        Ok(r)
+        parse_all(parser, s)
     }
 }
 
@@ -66,7 +81,7 @@ fn power_consumption(i: &Input) -> PowerConsumption {
     let vecs_length = i.vecs.len() as u64;
     let more_ones_than_zeroes_threshold = vecs_length / 2;
 
-    let most_common_bits = (0..i.bits).into_iter().fold(0u64, |acc, idx| {
+    let most_common_bits = (0..i.bits).fold(0u64, |acc, idx| {
         let sum_of_bits_at_idx = i.vecs.iter().fold(0, |acc, vec| {
             // direct vec idx is fine here because Input is parsed and all rows have the same length
             if vec[idx] {
@@ -117,7 +132,6 @@ where
     G: Fn(u64) -> R,
 {
     let filtered = (0..i.bits)
-        .into_iter()
         .fold_while(i.vecs.clone(), |acc, idx| {
             let zero_count_one_count = acc.iter().fold((0, 0), |(zero_count, one_count), vec| {
                 // direct vec idx is fine here because Input is parsed and all rows have the same length
@@ -139,7 +153,7 @@ where
             }
         })
         .into_inner();
-    let maybe_num = if let Some(v) = filtered.get(0) {
+    let maybe_num = if let Some(v) = filtered.first() {
         let num = v.iter().enumerate().fold(0u64, |acc, (idx, next)| {
             if *next {
                 let shift = (i.bits - 1 - idx) as u64;
@@ -1,23 +1,44 @@
 use std::collections::{HashMap, HashSet};
+use std::result::Result as StdResult;
 
-use anyhow::Result;
+use anyhow::{Context, Result};
+use combine::parser::char::*;
+use combine::*;
 use itertools::{FoldWhile, Itertools};
 
+use crate::common::{parse_from_line, sections, usize_parser};
+use crate::error;
+use crate::solution::Solution;
+
 const BINGO_COUNT: u8 = 5;
-const INPUT: &str = include_str!("../data/day_04_input");
-
-pub fn run() -> Result<()> {
-    println!("*** Day 4: Giant Squid ***");
-    println!("Input: {}", INPUT);
-    let input = parse(INPUT)?;
-    let solutions = winning_solutions(&input);
-    println!("Solution 1: {:?}\n", solutions.sol_1());
-    println!("Solution 2: {:?}\n", solutions.sol_2());
-    Ok(())
+pub const INPUT: &str = include_str!("../data/day_04_input");
+
+pub struct Day04;
+
+impl Solution for Day04 {
+    const DAY: usize = 4;
+    const TITLE: &'static str = "Giant Squid";
+    const INPUT: &'static str = INPUT;
+
+    type Input = Bingo;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(s: &str) -> Result<Self::Input> {
+        Ok(parse(s).map_err(|e| e.on_day(Self::DAY))?)
+    }
+
+    fn part_1(bingo: &Self::Input) -> Result<Self::Part1> {
+        winning_solutions(bingo).sol_1().context("No board won")
+    }
+
+    fn part_2(bingo: &Self::Input) -> Result<Self::Part2> {
+        winning_solutions(bingo).sol_2().context("No board won")
+    }
 }
 
 #[derive(Debug, Eq, PartialEq)]
-struct Bingo {
+pub struct Bingo {
     numbers_to_draw: NumbersToDraw,
     boards: Vec<Board>,
 }
@@ -48,61 +69,81 @@ impl Solutions {
     }
 }
 
-fn parse(s: &str) -> Result<Bingo> {
-    let by_section: Vec<_> = s.split("\n\n").collect();
-    let numbers_to_draw = if let Some(numbers_sec) = by_section.get(0) {
-        let numbers: Vec<usize> = numbers_sec
-            .split(',')
-            .filter_map(|n| n.parse().ok())
-            .collect();
-        NumbersToDraw(numbers)
-    } else {
-        bail!("Failed to parse numbers to draw")
-    };
+fn parse(s: &str) -> StdResult<Bingo, error::ParseError> {
+    let sections = sections(s);
+    let (numbers_line, numbers_sec) = sections
+        .first()
+        .ok_or_else(|| error::ParseError::at(s, 1, 1, 1, "No numbers to draw found"))?;
+    let numbers_to_draw = NumbersToDraw(parse_from_line(
+        sep_by1(usize_parser(), char(',')),
+        numbers_sec,
+        *numbers_line,
+    )?);
 
-    let boards: Vec<Vec<Vec<usize>>> = by_section
+    let boards = sections
         .iter()
         .skip(1)
-        .map(|board_s| {
+        .map(|(board_line, board_s)| {
+            let mut row_parser =
+                skip_many(char(' ')).with(sep_end_by1(usize_parser(), skip_many1(char(' '))));
             let numbers = board_s
                 .lines()
-                .map(|row| {
-                    let row_trimmed = row.trim();
-                    let numbers: Vec<usize> = row_trimmed
-                        .split(' ')
-                        .filter_map(|num_s| num_s.parse().ok())
-                        .collect();
-                    numbers
-                })
-                .collect();
-            numbers
+                .enumerate()
+                .map(|(row_idx, row)| parse_from_line(&mut row_parser, row, board_line + row_idx))
+                .collect::<StdResult<Vec<Vec<usize>>, _>>()?;
+            Ok((*board_line, board_s, Board { numbers }))
         })
-        .collect();
-
-    if let Some(first_board) = boards.get(0) {
-        let rows_count = first_board.len();
-        if let Some(first_row) = first_board.get(0) {
-            let cols_count = first_row.len();
-            if boards.iter().all(|board| {
-                board.len() == rows_count
-                    && board.get(0).map(|row| row.len()).unwrap_or(0) == cols_count
-            }) {
-                let boards = boards
-                    .into_iter()
-                    .map(|board| Board { numbers: board })
-                    .collect();
-                Ok(Bingo {
-                    numbers_to_draw,
-                    boards,
-                })
-            } else {
-                bail!("Non-rectangular input")
+        .collect::<StdResult<Vec<_>, _>>()?;
+
+    if let Some((_, _, first_board)) = boards.first() {
+        let rows_count = first_board.numbers.len();
+        let cols_count = first_board
+            .numbers
+            .first()
+            .map(|row| row.len())
+            .unwrap_or(0);
+        for (board_line, board_s, board) in boards.iter() {
+            if board.numbers.len() != rows_count {
+                return Err(error::ParseError::at(
+                    board_s,
+                    *board_line,
+                    *board_line,
+                    1,
+                    format!(
+                        "Non-rectangular input: board has {} rows, expected {}",
+                        board.numbers.len(),
+                        rows_count
+                    ),
+                ));
+            }
+            for (row_idx, row) in board.numbers.iter().enumerate() {
+                if row.len() != cols_count {
+                    return Err(error::ParseError::at(
+                        board_s,
+                        *board_line,
+                        board_line + row_idx,
+                        1,
+                        format!(
+                            "Non-rectangular input: row has {} numbers, expected {}",
+                            row.len(),
+                            cols_count
+                        ),
+                    ));
+                }
             }
-        } else {
-            bail!("No columns parsed")
         }
+        Ok(Bingo {
+            numbers_to_draw,
+            boards: boards.into_iter().map(|(_, _, board)| board).collect(),
+        })
     } else {
-        bail!("No boards parsed")
+        Err(error::ParseError::at(
+            s,
+            1,
+            numbers_line + numbers_sec.lines().count(),
+            1,
+            "No boards parsed",
+        ))
     }
 }
 
@@ -2,23 +2,37 @@ use std::fmt::{Display, Formatter};
 use std::result::Result as StdResult;
 
 use anyhow::Result;
-use combine::easy;
 use combine::parser::char::*;
 use combine::*;
 
-use crate::common::usize_parser;
+use crate::common::{parse_all, usize_parser};
+use crate::error;
+use crate::solution::Solution;
 
-const INPUT: &str = include_str!("../data/day_05_input");
+pub const INPUT: &str = include_str!("../data/day_05_input");
 
-pub fn run() -> Result<()> {
-    println!("*** Day 5: Hydrothermal Venture ***");
-    println!("Input: {}", INPUT);
-    let input = Input::parse(INPUT)?;
-    let part_1_diagram = part_1_diagram(&input);
-    println!("Solution 1: {:?}\n", part_1_diagram.count_overlaps());
-    let part_2_diagram = part_2_diagram(&input);
-    println!("Solution 2: {:?}\n", part_2_diagram.count_overlaps());
-    Ok(())
+pub struct Day05;
+
+impl Solution for Day05 {
+    const DAY: usize = 5;
+    const TITLE: &'static str = "Hydrothermal Venture";
+    const INPUT: &'static str = INPUT;
+
+    type Input = Input;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(s: &str) -> Result<Self::Input> {
+        Ok(Input::parse(s).map_err(|e| e.on_day(Self::DAY))?)
+    }
+
+    fn part_1(input: &Self::Input) -> Result<Self::Part1> {
+        Ok(part_1_diagram(input).count_overlaps())
+    }
+
+    fn part_2(input: &Self::Input) -> Result<Self::Part2> {
+        Ok(part_2_diagram(input).count_overlaps())
+    }
 }
 
 #[derive(Debug, Eq, PartialEq)]
@@ -34,7 +48,7 @@ struct Line {
 }
 
 #[derive(Debug, Eq, PartialEq)]
-struct Input {
+pub struct Input {
     lines: Vec<Line>,
     max: Point,
 }
@@ -70,7 +84,7 @@ impl Display for Diagram {
 }
 
 impl Input {
-    fn parse(s: &str) -> StdResult<Input, easy::ParseError<&str>> {
+    fn parse(s: &str) -> StdResult<Input, error::ParseError> {
         macro_rules! point_parser {
             () => {
                 usize_parser().skip(char(',')).and(usize_parser())
@@ -88,12 +102,11 @@ impl Input {
                 },
                 end: Point { x: end_x, y: end_y },
             });
-        let mut parser = many1(line_parser.skip(spaces())).map(|lines: Vec<Line>| {
+        let parser = many1(line_parser.skip(spaces())).map(|lines: Vec<Line>| {
             let max = max_point(lines.as_slice());
             Input { lines, max }
         });
-        let (r, _) = parser.easy_parse(s)?;
-        Ok(r)
+        parse_all(parser, s)
     }
 }
 
@@ -4,25 +4,40 @@ use anyhow::Result;
 use combine::parser::char::*;
 use combine::*;
 
-use crate::common::usize_parser;
+use crate::common::{parse_all, usize_parser};
+use crate::error;
+use crate::solution::Solution;
 
-const INPUT: &str = include_str!("../data/day_06_input");
+pub const INPUT: &str = include_str!("../data/day_06_input");
 const BORN_WITH_TIMER: usize = 8;
 const TIMER_AFTER_GIVING_BIRTH: usize = 6;
 
-pub fn run() -> Result<()> {
-    println!("*** Day 6: Lanternfish ***");
-    println!("Input: {}", INPUT);
-    let input = parse(INPUT)?;
-    let part_1_diagram = part_1_play(&input, 80);
-    println!("Solution 1: {:?}\n", part_1_diagram.count_fishes());
-    let part_2_diagram = part_1_play(&input, 256);
-    println!("Solution 2: {:?}\n", part_2_diagram.count_fishes());
-    Ok(())
+pub struct Day06;
+
+impl Solution for Day06 {
+    const DAY: usize = 6;
+    const TITLE: &'static str = "Lanternfish";
+    const INPUT: &'static str = INPUT;
+
+    type Input = Input;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(s: &str) -> Result<Self::Input> {
+        Ok(parse(s).map_err(|e| e.on_day(Self::DAY))?)
+    }
+
+    fn part_1(input: &Self::Input) -> Result<Self::Part1> {
+        Ok(part_1_play(input, 80).count_fishes())
+    }
+
+    fn part_2(input: &Self::Input) -> Result<Self::Part2> {
+        Ok(part_1_play(input, 256).count_fishes())
+    }
 }
 
 #[derive(Debug, Eq, PartialEq)]
-struct Input(Vec<usize>);
+pub struct Input(Vec<usize>);
 
 #[derive(Debug, Eq, PartialEq)]
 struct State(Vec<usize>);
@@ -33,10 +48,9 @@ impl State {
     }
 }
 
-fn parse(s: &str) -> StdResult<Input, easy::ParseError<&str>> {
-    let mut parser = sep_by1(usize_parser(), char(',')).map(Input);
-    let (r, _) = parser.easy_parse(s)?;
-    Ok(r)
+fn parse(s: &str) -> StdResult<Input, error::ParseError> {
+    let parser = sep_by1(usize_parser(), char(',')).map(Input);
+    parse_all(parser, s)
 }
 
 fn part_1_play(i: &Input, days_to_play: usize) -> State {
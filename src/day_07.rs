@@ -2,24 +2,43 @@ use std::collections::HashMap;
 use std::convert::identity;
 use std::result::Result as StdResult;
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use combine::parser::char::*;
 use combine::*;
 use itertools::Itertools;
 
-use crate::common::usize_parser;
+use crate::common::{parse_all, usize_parser};
+use crate::error;
+use crate::solution::Solution;
 
-const INPUT: &str = include_str!("../data/day_07_input");
+pub const INPUT: &str = include_str!("../data/day_07_input");
 
-pub fn run() -> Result<()> {
-    println!("*** Day 7: The Treachery of Whales ***");
-    println!("Input: {}", INPUT);
-    let input = parse(INPUT)?;
-    let sol_1 = solve_part_1(&input);
-    println!("Solution 1: {:?}", sol_1);
-    let sol_2 = solve_part_2(&input);
-    println!("Solution 2: {:?}", sol_2);
-    Ok(())
+pub struct Day07;
+
+impl Solution for Day07 {
+    const DAY: usize = 7;
+    const TITLE: &'static str = "The Treachery of Whales";
+    const INPUT: &'static str = INPUT;
+
+    type Input = Input;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(s: &str) -> Result<Self::Input> {
+        Ok(parse(s).map_err(|e| e.on_day(Self::DAY))?)
+    }
+
+    fn part_1(input: &Self::Input) -> Result<Self::Part1> {
+        solve_part_1(input)
+            .map(|(_, cost)| cost.0)
+            .context("No crabs to align")
+    }
+
+    fn part_2(input: &Self::Input) -> Result<Self::Part2> {
+        solve_part_2(input)
+            .map(|(_, cost)| cost.0)
+            .context("No crabs to align")
+    }
 }
 
 #[derive(Debug, Hash, Eq, PartialEq)]
@@ -29,12 +48,11 @@ struct Pos(usize);
 struct TotalFuelCost(usize);
 
 #[derive(Debug, Eq, PartialEq)]
-struct Input(Vec<Pos>);
+pub struct Input(Vec<Pos>);
 
-fn parse(s: &str) -> StdResult<Input, easy::ParseError<&str>> {
-    let mut parser = sep_by1(usize_parser().map(Pos), char(',')).map(Input);
-    let (r, _) = parser.easy_parse(s)?;
-    Ok(r)
+fn parse(s: &str) -> StdResult<Input, error::ParseError> {
+    let parser = sep_by1(usize_parser().map(Pos), char(',')).map(Input);
+    parse_all(parser, s)
 }
 
 fn solve_part_1(i: &Input) -> Option<(Pos, TotalFuelCost)> {
@@ -67,7 +85,7 @@ where
             let total_fuel_cost_for_pos_candidate =
                 pos_to_counts.iter().fold(0, |acc, (pos, crab_counts)| {
                     let distance_from_candidate =
-                        (pos.0 as isize - pos_candidate as isize).abs() as usize;
+                        (pos.0 as isize - pos_candidate as isize).unsigned_abs();
                     let total_fuel_cost =
                         distance_to_fuel_cost(distance_from_candidate) * crab_counts;
                     acc + total_fuel_cost
@@ -10,17 +10,34 @@ use std::fmt::{Display, Formatter};
 use std::iter::FromIterator;
 use Segment::*;
 
-const INPUT: &str = include_str!("../data/day_08_input");
-
-pub fn run() -> Result<()> {
-    println!("*** Day 8: Seven Segment Search ***");
-    println!("Input: {}", INPUT);
-    let input = parse(INPUT)?;
-    let sol_1 = unique_number_of_segments_in_output_count(&input);
-    println!("Solution 1: {:?}", sol_1);
-    let sol_2 = input.solve()?;
-    println!("Solution 2: {:?}", sol_2);
-    Ok(())
+use crate::common::parse_all;
+use crate::error;
+use crate::solution::Solution;
+
+pub const INPUT: &str = include_str!("../data/day_08_input");
+
+pub struct Day08;
+
+impl Solution for Day08 {
+    const DAY: usize = 8;
+    const TITLE: &'static str = "Seven Segment Search";
+    const INPUT: &'static str = INPUT;
+
+    type Input = Input;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(s: &str) -> Result<Self::Input> {
+        Ok(parse(s).map_err(|e| e.on_day(Self::DAY))?)
+    }
+
+    fn part_1(input: &Self::Input) -> Result<Self::Part1> {
+        Ok(unique_number_of_segments_in_output_count(input))
+    }
+
+    fn part_2(input: &Self::Input) -> Result<Self::Part2> {
+        input.solve()
+    }
 }
 
 #[derive(Debug, Eq, PartialEq)]
@@ -33,7 +50,7 @@ struct Line {
 }
 
 #[derive(Debug, Eq, PartialEq)]
-struct Input(Vec<Line>);
+pub struct Input(Vec<Line>);
 
 impl Input {
     fn solve(&self) -> Result<usize> {
@@ -174,7 +191,7 @@ impl Display for OngoingMapping {
     }
 }
 
-fn parse(s: &str) -> StdResult<Input, easy::ParseError<&str>> {
+fn parse(s: &str) -> StdResult<Input, error::ParseError> {
     let line_parser = sep_by1(segments_parser(), char(' '))
         .skip(string("| "))
         .and(sep_by1(segments_parser(), char(' ')))
@@ -202,9 +219,8 @@ fn parse(s: &str) -> StdResult<Input, easy::ParseError<&str>> {
                 outputs: non_empty_outputs,
             }
         });
-    let mut parser = many1(line_parser.skip(spaces())).map(Input);
-    let (r, _) = parser.easy_parse(s)?;
-    Ok(r)
+    let parser = many1(line_parser.skip(spaces())).map(Input);
+    parse_all(parser, s)
 }
 
 // Count numbers that can be 1, 4, 7, or 8 since they have unique segment counts
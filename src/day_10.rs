@@ -1,6 +1,6 @@
 use std::result::Result as StdResult;
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use combine::parser::char::*;
 use combine::*;
 use itertools::{FoldWhile, Itertools};
@@ -8,21 +8,38 @@ use itertools::{FoldWhile, Itertools};
 use Bracket::*;
 use Char::*;
 
-const INPUT: &str = include_str!("../data/day_10_input");
+use crate::common::parse_all;
+use crate::error;
+use crate::solution::Solution;
 
-pub fn run() -> Result<()> {
-    println!("*** Day 10: Syntax Scoring ***");
-    println!("Input: {}", INPUT);
-    let input = parse(INPUT)?;
-    let sol_1 = sol_1(&input);
-    println!("Solution 1: {:?}", sol_1);
-    let sol_2 = sol_2(&input);
-    println!("Solution 2: {:?}", sol_2);
-    Ok(())
+pub const INPUT: &str = include_str!("../data/day_10_input");
+
+pub struct Day10;
+
+impl Solution for Day10 {
+    const DAY: usize = 10;
+    const TITLE: &'static str = "Syntax Scoring";
+    const INPUT: &'static str = INPUT;
+
+    type Input = Input;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(s: &str) -> Result<Self::Input> {
+        Ok(parse(s).map_err(|e| e.on_day(Self::DAY))?)
+    }
+
+    fn part_1(input: &Self::Input) -> Result<Self::Part1> {
+        Ok(sol_1(input))
+    }
+
+    fn part_2(input: &Self::Input) -> Result<Self::Part2> {
+        sol_2(input).context("No incomplete lines found")
+    }
 }
 
 #[derive(Debug, Eq, PartialEq)]
-struct Input(Vec<Vec<Char>>);
+pub struct Input(Vec<Vec<Char>>);
 
 #[derive(Debug, Eq, PartialEq)]
 enum Char {
@@ -124,11 +141,10 @@ fn find_first_illegal(line: &[Char]) -> (Vec<&Bracket>, Option<&Bracket>) {
     (stack, illegal_closer)
 }
 
-fn parse(s: &str) -> StdResult<Input, easy::ParseError<&str>> {
+fn parse(s: &str) -> StdResult<Input, error::ParseError> {
     let line_parser = many1(char_parser());
-    let mut parser = many1(line_parser.skip(spaces())).map(Input);
-    let (r, _) = parser.easy_parse(s)?;
-    Ok(r)
+    let parser = many1(line_parser.skip(spaces())).map(Input);
+    parse_all(parser, s)
 }
 
 fn char_parser<Input>() -> impl Parser<Input, Output = Char>
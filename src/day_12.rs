@@ -9,18 +9,35 @@ use combine::*;
 use std::fmt::{Display, Formatter};
 use Point::*;
 
+use crate::common::parse_all;
+use crate::error;
+use crate::solution::Solution;
+
 pub const INPUT: &str = include_str!("../data/day_12_input");
 const THRESHOLD_FOR_REPEATABLE: usize = 2;
 
-pub fn run() -> Result<()> {
-    println!("*** Day 12: Passage Pathing ***");
-    println!("Input: {}", INPUT);
-    let input = parse(INPUT)?;
-    let sol_1 = generate_paths(&input, false)?;
-    println!("Solution 1: {:?}", sol_1.len());
-    let sol_2 = generate_paths(&input, true)?;
-    println!("Solution 2: {:?}", sol_2.len());
-    Ok(())
+pub struct Day12;
+
+impl Solution for Day12 {
+    const DAY: usize = 12;
+    const TITLE: &'static str = "Passage Pathing";
+    const INPUT: &'static str = INPUT;
+
+    type Input = Input;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(s: &str) -> Result<Self::Input> {
+        Ok(parse(s).map_err(|e| e.on_day(Self::DAY))?)
+    }
+
+    fn part_1(input: &Self::Input) -> Result<Self::Part1> {
+        Ok(generate_paths(input, false)?.len())
+    }
+
+    fn part_2(input: &Self::Input) -> Result<Self::Part2> {
+        Ok(generate_paths(input, true)?.len())
+    }
 }
 
 #[derive(Debug, Eq, PartialEq)]
@@ -58,7 +75,7 @@ pub enum Point {
     End,
 }
 
-pub fn parse(s: &str) -> StdResult<Input, easy::ParseError<&str>> {
+pub fn parse(s: &str) -> StdResult<Input, error::ParseError> {
     macro_rules! point_parser {
         () => {
             many1(letter()).map(|s: String| {
@@ -77,7 +94,7 @@ pub fn parse(s: &str) -> StdResult<Input, easy::ParseError<&str>> {
         };
     }
     let line_parser = point_parser!().skip(char('-')).and(point_parser!());
-    let mut parser = many1(line_parser.skip(spaces())).map(|lines: Vec<(Point, Point)>| {
+    let parser = many1(line_parser.skip(spaces())).map(|lines: Vec<(Point, Point)>| {
         let mut connections = HashMap::with_capacity(lines.len());
         for (from, to) in lines.into_iter() {
             connections
@@ -92,8 +109,7 @@ pub fn parse(s: &str) -> StdResult<Input, easy::ParseError<&str>> {
         Input(connections)
     });
 
-    let (r, _) = parser.easy_parse(s)?;
-    Ok(r)
+    parse_all(parser, s)
 }
 
 pub fn generate_paths(
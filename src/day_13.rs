@@ -1,29 +1,45 @@
 use std::collections::HashSet;
 use std::fmt::{Display, Formatter};
+use std::result::Result as StdResult;
 
 use anyhow::{Context, Result};
 use combine::parser::char::*;
 use combine::*;
 
-use crate::common::usize_parser;
+use crate::common::{parse_from_line, sections, usize_parser};
+use crate::error;
+use crate::solution::Solution;
 
 pub const INPUT: &str = include_str!("../data/day_13_input");
 
-pub fn run() -> Result<()> {
-    println!("*** Day 13: Transparent Origami ***");
-    println!("Input: {}", INPUT);
-    let input = parse(INPUT)?;
-    let first_fold_along = input
-        .fold_alongs
-        .first()
-        .context("Should have a first fold !??!")?;
-    let sol_1 = fold_along(&input.dots, &input.max_coords, first_fold_along);
-    println!("Solution 1: {:?}", sol_1.dots.len());
-
-    let sol_2 = input.fold_all();
-    println!("Solution 2");
-    println!("{}", sol_2);
-    Ok(())
+pub struct Day13;
+
+impl Solution for Day13 {
+    const DAY: usize = 13;
+    const TITLE: &'static str = "Transparent Origami";
+    const INPUT: &'static str = INPUT;
+
+    type Input = Input;
+    type Part1 = usize;
+    type Part2 = FoldOutput;
+
+    fn parse(s: &str) -> Result<Self::Input> {
+        Ok(parse(s).map_err(|e| e.on_day(Self::DAY))?)
+    }
+
+    fn part_1(input: &Self::Input) -> Result<Self::Part1> {
+        let first_fold_along = input
+            .fold_alongs
+            .first()
+            .context("Should have a first fold !??!")?;
+        Ok(fold_along(&input.dots, &input.max_coords, first_fold_along)
+            .dots
+            .len())
+    }
+
+    fn part_2(input: &Self::Input) -> Result<Self::Part2> {
+        Ok(input.fold_all())
+    }
 }
 
 #[derive(Debug, Eq, PartialEq, Copy, Clone, Hash, Ord, PartialOrd)]
@@ -158,43 +174,43 @@ fn max_coords(dots: &HashSet<Coords>) -> Coords {
 
 // Ugh, the double newline separator still messes with me...
 // TODO: try with pure Combine, again (https://gitter.im/Marwes/combine?at=5fde845622f12e449bfe9459)
-pub fn parse(s: &str) -> Result<Input> {
-    let mut split_by_newline = s.trim().split("\n\n"); // ugh.... really need to figure out how to do this with just combine...
-
-    let dots_str = split_by_newline.next().context("Dots section found")?;
-
-    let dots: HashSet<Coords> = dots_str
-        .lines()
-        .filter_map(|dot_str| {
-            let mut dot_parser = usize_parser()
-                .skip(char(','))
-                .and(usize_parser())
-                .map(|(x, y)| Coords { x, y });
-            let (dot, _) = dot_parser.easy_parse(dot_str.trim()).ok()?;
-            Some(dot)
-        })
-        .collect();
+pub fn parse(s: &str) -> StdResult<Input, error::ParseError> {
+    let sections = sections(s);
+    let mut sections_iter = sections.iter();
+
+    let (dots_line, dots_str) = sections_iter
+        .next()
+        .ok_or_else(|| error::ParseError::at(s, 1, 1, 1, "No dots section found"))?;
+    let dot_parser = usize_parser()
+        .skip(char(','))
+        .and(usize_parser())
+        .map(|(x, y)| Coords { x, y });
+    let dots: HashSet<Coords> =
+        parse_from_line(sep_by1(dot_parser, newline()), dots_str, *dots_line)?;
 
     let max_coords = max_coords(&dots);
 
-    let fold_alongs_str = split_by_newline
-        .next()
-        .context("No fold along section found")?;
-
-    let fold_alongs = fold_alongs_str
-        .lines()
-        .filter_map(|fold_along_str| {
-            let mut fold_along_parser = string("fold along ").with(
-                attempt(string("x=").with(usize_parser()))
-                    .map(FoldAlong::X)
-                    .or(attempt(
-                        attempt(string("y=").with(usize_parser())).map(FoldAlong::Y),
-                    )),
-            );
-            let (fold_along, _) = fold_along_parser.easy_parse(fold_along_str.trim()).ok()?;
-            Some(fold_along)
-        })
-        .collect();
+    let (fold_alongs_line, fold_alongs_str) = sections_iter.next().ok_or_else(|| {
+        error::ParseError::at(
+            s,
+            1,
+            dots_line + dots_str.lines().count(),
+            1,
+            "No fold along section found",
+        )
+    })?;
+    let fold_along_parser = string("fold along ").with(
+        attempt(string("x=").with(usize_parser()))
+            .map(FoldAlong::X)
+            .or(attempt(
+                attempt(string("y=").with(usize_parser())).map(FoldAlong::Y),
+            )),
+    );
+    let fold_alongs = parse_from_line(
+        sep_by1(fold_along_parser, newline()),
+        fold_alongs_str,
+        *fold_alongs_line,
+    )?;
 
     let r = Input {
         dots,
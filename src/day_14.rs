@@ -1,26 +1,44 @@
 use std::collections::HashMap;
 use std::result::Result as StdResult;
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use combine::parser::char::*;
 use combine::*;
 
+use crate::common::parse_all;
+use crate::error;
+use crate::solution::Solution;
+
 pub const INPUT: &str = include_str!("../data/day_14_input");
 
 const DUMMY_CHAR: char = '!';
 
-pub fn run() -> Result<()> {
-    println!("*** Day 14: Extended Polymerization ***");
-    println!("Input: {}", INPUT);
-    let input = parse(INPUT)?;
+pub struct Day14;
+
+impl Solution for Day14 {
+    const DAY: usize = 14;
+    const TITLE: &'static str = "Extended Polymerization";
+    const INPUT: &'static str = INPUT;
+
+    type Input = Input;
+    type Part1 = usize;
+    type Part2 = usize;
 
-    let sol_1 = input.max_minus_least_after_steps(10);
-    println!("Solution 1: {:?}", sol_1);
+    fn parse(s: &str) -> Result<Self::Input> {
+        Ok(parse(s).map_err(|e| e.on_day(Self::DAY))?)
+    }
 
-    let sol_2 = input.max_minus_least_after_steps(40);
-    println!("Solution 2: {:?}", sol_2);
+    fn part_1(input: &Self::Input) -> Result<Self::Part1> {
+        input
+            .max_minus_least_after_steps(10)
+            .context("Empty polymer")
+    }
 
-    Ok(())
+    fn part_2(input: &Self::Input) -> Result<Self::Part2> {
+        input
+            .max_minus_least_after_steps(40)
+            .context("Empty polymer")
+    }
 }
 
 #[derive(Debug, Eq, PartialEq)]
@@ -129,7 +147,7 @@ impl Input {
     }
 }
 
-pub fn parse(s: &str) -> StdResult<Input, easy::ParseError<&str>> {
+pub fn parse(s: &str) -> StdResult<Input, error::ParseError> {
     let template_parser = many1(upper()).map(Template);
     let mapping_parser = upper()
         .and(upper())
@@ -139,12 +157,11 @@ pub fn parse(s: &str) -> StdResult<Input, easy::ParseError<&str>> {
     let mappings_parser = many1(mapping_parser.skip(spaces()))
         .map(|mappings: Vec<(Pair, char)>| mappings.into_iter().collect::<HashMap<Pair, char>>());
 
-    let mut parser = template_parser
+    let parser = template_parser
         .skip(spaces())
         .and(mappings_parser)
         .map(|(template, mappings)| Input { template, mappings });
-    let (r, _) = parser.easy_parse(s)?;
-    Ok(r)
+    parse_all(parser, s)
 }
 
 #[cfg(test)]
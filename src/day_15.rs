@@ -1,215 +1,185 @@
-use std::cmp::Ordering;
-use std::collections::BinaryHeap;
+use std::collections::HashSet;
+use std::fmt::{Display, Formatter};
 use std::result::Result as StdResult;
 
-use anyhow::Result;
-use combine::easy::{Error, Info};
-use combine::parser::char::*;
-use combine::*;
+use anyhow::{Context, Result};
+
+use crate::error;
+use crate::grid::{Coords, Grid};
+use crate::search::{self, Path};
+use crate::solution::Solution;
 
 pub const INPUT: &str = include_str!("../data/day_15_input");
 
-const UNEXPLORED_RISK: usize = usize::MAX;
+// ANSI escapes for highlighting the path in a terminal
+const BOLD: &str = "\x1b[1m";
+const RESET: &str = "\x1b[0m";
 
-pub fn run() -> Result<()> {
-    println!("*** Day 15: Chiton ***");
-    println!("Input: {}", INPUT);
-    let input = parse(INPUT)?;
-    let sol_1 = input.lowest_risk_to_end();
-    println!("Solution 1: {:?}", sol_1);
+pub struct Day15;
 
-    let expanded_input = input.expand(5);
-    let sol_2 = expanded_input.lowest_risk_to_end();
-    println!("Solution 2: {:?}", sol_2);
-    Ok(())
-}
+impl Solution for Day15 {
+    const DAY: usize = 15;
+    const TITLE: &'static str = "Chiton";
+    const INPUT: &'static str = INPUT;
 
-#[derive(Debug, Eq, PartialEq)]
-pub struct Input {
-    rows: Vec<Vec<usize>>,
-    max_coords: Coords,
-}
+    type Input = Input;
+    type Part1 = usize;
+    type Part2 = usize;
 
-#[derive(Copy, Clone, Eq, PartialEq)]
-struct RiskToPosition {
-    risk: usize,
-    coords_idx: CoordsIdx,
-}
+    fn parse(s: &str) -> Result<Self::Input> {
+        Ok(parse(s).map_err(|e| e.on_day(Self::DAY))?)
+    }
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd)]
-struct CoordsIdx(usize);
-
-// The priority queue depends on `Ord`.
-// Explicitly implement the trait so the queue becomes a min-heap
-// instead of a max-heap.
-impl Ord for RiskToPosition {
-    fn cmp(&self, other: &Self) -> Ordering {
-        // Notice that the we flip the ordering on risk.
-        // In case of a tie we compare positions - this step is necessary
-        // to make implementations of `PartialEq` and `Ord` consistent.
-        other
-            .risk
-            .cmp(&self.risk)
-            .then_with(|| self.coords_idx.cmp(&other.coords_idx))
+    fn part_1(input: &Self::Input) -> Result<Self::Part1> {
+        input.lowest_risk_to_end().context("No path to the end")
     }
-}
 
-// `PartialOrd` needs to be implemented as well.
-impl PartialOrd for RiskToPosition {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
+    fn part_2(input: &Self::Input) -> Result<Self::Part2> {
+        input
+            .expand(5)
+            .lowest_risk_to_end()
+            .context("No path to the end")
     }
 }
 
+#[derive(Debug, Eq, PartialEq)]
+pub struct Input {
+    risks: Grid<usize>,
+}
+
 impl Input {
     pub fn lowest_risk_to_end(&self) -> Option<usize> {
-        // Read the docs for BinaryHeap :p
-        let start = Coords { row: 0, col: 0 };
-        let start_coords_idx = self.to_coords_idx(start);
-        let end = self.max_coords;
-
-        // risk_from_start[coords_idx] = current lowest risk from `start` to `coords`
-        let mut risk_from_start: Vec<usize> =
-            vec![UNEXPLORED_RISK; (self.max_coords.row + 1) * (self.max_coords.col + 1)];
-
-        let mut to_visit_prioritised_q = BinaryHeap::new();
-
-        // We're at `start`, with a zero risk
-        risk_from_start[start_coords_idx.0] = 0;
-        to_visit_prioritised_q.push(RiskToPosition {
-            risk: 0,
-            coords_idx: self.to_coords_idx(start),
-        });
-
-        // Examine the frontier with lower risk positions first
-        while let Some(RiskToPosition { risk, coords_idx }) = to_visit_prioritised_q.pop() {
-            // Done; just exit
-            let coords = self.to_coords(coords_idx);
-            if coords == end {
-                return Some(risk);
-            } else {
-                let known_lowest_risk_to_current = risk_from_start[coords_idx.0];
-                if risk > known_lowest_risk_to_current {
-                    // Skip exploring this path; we already know of a less risky way of getting here
-                    continue;
-                } else {
-                    // For each coords we can reach, see if we can find a way with
-                    // a lower risk going through some new coords
-                    for adjacent in self.adjacents(&coords) {
-                        let adjacent_coords_idx = self.to_coords_idx(adjacent);
-                        let current_known_lowest_risk_to_adjacent =
-                            risk_from_start[adjacent_coords_idx.0];
-                        let next_tally = RiskToPosition {
-                            risk: risk + self.rows[adjacent.row][adjacent.col],
-                            coords_idx: adjacent_coords_idx,
-                        };
-                        // If so, add it to the frontier and continue
-                        if next_tally.risk < current_known_lowest_risk_to_adjacent {
-                            to_visit_prioritised_q.push(next_tally);
-                            risk_from_start[adjacent_coords_idx.0] = next_tally.risk;
-                        }
-                    }
-                }
-            }
-        }
+        self.lowest_risk_path_to_end().map(|path| path.cost)
+    }
 
-        // End not reachable
-        None
+    /// The least risky way from the top left to the bottom right, along with its total risk
+    pub fn lowest_risk_path_to_end(&self) -> Option<Path<Coords>> {
+        self.lowest_risk_path(&PathOptions::default())
     }
 
-    pub fn expand(&self, expand_factor: usize) -> Input {
-        let expanded_rows = (0..expand_factor)
-            .flat_map(|row_exp_idx| {
-                self.rows.iter().map(move |row| {
-                    (0..expand_factor)
-                        .flat_map(move |col_exp_idx| {
-                            row.iter().map(move |v| {
-                                let next_value = v + col_exp_idx + row_exp_idx;
-                                if next_value > 9 {
-                                    next_value % 9
-                                } else {
-                                    next_value
-                                }
-                            })
-                        })
-                        .collect::<Vec<_>>()
-                })
-            })
-            .collect::<Vec<Vec<_>>>();
-        let expanded_max_coords = Coords {
-            row: (self.max_coords.row + 1) * expand_factor - 1,
-            col: (self.max_coords.col + 1) * expand_factor - 1,
-        };
-        Input {
-            rows: expanded_rows,
-            max_coords: expanded_max_coords,
+    /// The least risky path according to `options`.
+    ///
+    /// None if there is no path, or either end is outside the cave.
+    pub fn lowest_risk_path(&self, options: &PathOptions) -> Option<Path<Coords>> {
+        let start = options.from.unwrap_or(Coords { row: 0, col: 0 });
+        let end = options.to.unwrap_or_else(|| self.risks.max_coords());
+        if !self.risks.contains(start) || !self.risks.contains(end) {
+            return None;
         }
+        search::dijkstra(
+            start,
+            |coords| {
+                let from = *coords;
+                let adjacents: Vec<_> = match options.moves {
+                    Moves::Orthogonal => self.risks.neighbours_4(from).collect(),
+                    Moves::WithDiagonals => self.risks.neighbours_8(from).collect(),
+                };
+                adjacents.into_iter().map(move |adjacent| {
+                    let cost = options.cost.of(self.risks[from], self.risks[adjacent]);
+                    (adjacent, cost)
+                })
+            },
+            |coords| *coords == end,
+        )
     }
 
-    fn to_coords_idx(&self, Coords { row, col }: Coords) -> CoordsIdx {
-        let idx = row * (self.max_coords.col + 1) + col;
-        CoordsIdx(idx)
+    /// Shows the cave with only the risks along `path`, e.g. one from [Input::lowest_risk_path_to_end].
+    ///
+    /// The alternate format (`{:#}`) shows every risk instead, with those on the path in bold.
+    pub fn render_path(&self, path: &[Coords]) -> RenderedPath<'_> {
+        RenderedPath {
+            risks: &self.risks,
+            on_path: path.iter().copied().collect(),
+        }
     }
 
-    fn to_coords(&self, CoordsIdx(idx): CoordsIdx) -> Coords {
-        let row = idx / (self.max_coords.col + 1);
-        let col = idx % (self.max_coords.col + 1);
-        Coords { row, col }
+    pub fn expand(&self, expand_factor: usize) -> Input {
+        let (rows, columns) = (self.risks.rows(), self.risks.columns());
+        let risks = Grid::from_fn(
+            rows * expand_factor,
+            columns * expand_factor,
+            |Coords { row, col }| {
+                let v = self.risks[Coords {
+                    row: row % rows,
+                    col: col % columns,
+                }];
+                let next_value = v + row / rows + col / columns;
+                if next_value > 9 {
+                    next_value % 9
+                } else {
+                    next_value
+                }
+            },
+        );
+        Input { risks }
     }
+}
 
-    fn adjacents(&self, Coords { row, col }: &Coords) -> Vec<Coords> {
-        let row_i = *row as isize;
-        let col_i = *col as isize;
-        if *row <= self.max_coords.row && *col <= self.max_coords.col {
-            vec![
-                (row_i - 1, col_i), // N
-                (row_i, col_i - 1), // W
-                (row_i, col_i + 1), // E
-                (row_i + 1, col_i), // S
-            ]
-            .into_iter()
-            .filter(|(r, c)| {
-                !(*r < 0
-                    || *c < 0
-                    || *r > self.max_coords.row as isize
-                    || *c > self.max_coords.col as isize)
-            })
-            .map(|(row, col)| Coords {
-                row: row as usize,
-                col: col as usize,
-            })
-            .collect()
-        } else {
-            vec![]
+/// How to get around the cave. The default is the puzzle's: from the top left to the bottom
+/// right, moving up, down, left or right, paying the risk of each position entered.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct PathOptions {
+    /// Defaults to the top left
+    pub from: Option<Coords>,
+    /// Defaults to the bottom right
+    pub to: Option<Coords>,
+    pub moves: Moves,
+    pub cost: Cost,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+pub enum Moves {
+    #[default]
+    Orthogonal,
+    WithDiagonals,
+}
+
+/// What moving from one position to the next costs
+#[derive(Debug, Copy, Clone, Default)]
+pub enum Cost {
+    /// The risk of the position moved to
+    #[default]
+    Entering,
+    /// The difference between the risks of the two positions
+    Difference,
+    /// Given the risks of the position moved from and the one moved to
+    Custom(fn(usize, usize) -> usize),
+}
+
+impl Cost {
+    fn of(&self, from_risk: usize, to_risk: usize) -> usize {
+        match self {
+            Cost::Entering => to_risk,
+            Cost::Difference => from_risk.abs_diff(to_risk),
+            Cost::Custom(f) => f(from_risk, to_risk),
         }
     }
 }
 
-#[derive(Debug, Eq, PartialEq, Copy, Clone, Ord, PartialOrd)]
-pub struct Coords {
-    row: usize,
-    col: usize,
+pub struct RenderedPath<'a> {
+    risks: &'a Grid<usize>,
+    on_path: HashSet<Coords>,
 }
 
-pub fn parse(s: &str) -> StdResult<Input, easy::ParseError<&str>> {
-    let line_parser = many1(digit().and_then(|n_s| n_s.to_string().parse::<usize>()));
-    let mut parser = many1(line_parser.skip(spaces())).and_then(|rows: Vec<Vec<usize>>| {
-        let first_row_length = rows[0].len(); // our parser ensures at least 1
-        let all_rows_same_length = rows.iter().all(|row| row.len() == first_row_length);
-        if all_rows_same_length {
-            let max_coords = Coords {
-                row: rows.len() - 1,
-                col: first_row_length - 1,
-            };
-            Ok(Input { rows, max_coords })
-        } else {
-            Err(Error::Unexpected(Info::Static(
-                "Not all rows have the same number of columns",
-            )))
+impl Display for RenderedPath<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (coords, risk) in self.risks.iter() {
+            if coords.row > 0 && coords.col == 0 {
+                writeln!(f)?;
+            }
+            match (self.on_path.contains(&coords), f.alternate()) {
+                (true, true) => write!(f, "{}{}{}", BOLD, risk, RESET)?,
+                (true, false) | (false, true) => write!(f, "{}", risk)?,
+                (false, false) => write!(f, ".")?,
+            }
         }
-    });
-    let (r, _) = parser.easy_parse(s)?;
-    Ok(r)
+        Ok(())
+    }
+}
+
+pub fn parse(s: &str) -> StdResult<Input, error::ParseError> {
+    let risks = Grid::parse_digits(s)?;
+    Ok(Input { risks })
 }
 
 #[cfg(test)]
@@ -243,17 +213,75 @@ mod tests {
     }
 
     #[test]
-    fn to_coords_ix_test() {
+    fn lowest_risk_path_to_end_test() {
         let i = parse(TEST_INPUT).unwrap();
-        let idx = i.to_coords_idx(Coords { row: 1, col: 0 });
-        assert_eq!(CoordsIdx(10), idx);
+        let path = i.lowest_risk_path_to_end().unwrap();
+        assert_eq!(40, path.cost);
+        assert_eq!(Some(&Coords { row: 0, col: 0 }), path.nodes.first());
+        assert_eq!(Some(&Coords { row: 9, col: 9 }), path.nodes.last());
+        // The start isn't entered, so doesn't count
+        let risk_along_path: usize = path.nodes.iter().skip(1).map(|c| i.risks[*c]).sum();
+        assert_eq!(40, risk_along_path);
+
+        let expanded = i.expand(5);
+        let path = expanded.lowest_risk_path_to_end().unwrap();
+        assert_eq!(315, path.cost);
+        assert_eq!(Some(&Coords { row: 49, col: 49 }), path.nodes.last());
     }
 
     #[test]
-    fn to_coords_test() {
-        let i = parse(TEST_INPUT).unwrap();
-        let coords = i.to_coords(CoordsIdx(13));
-        assert_eq!(Coords { row: 1, col: 3 }, coords);
+    fn lowest_risk_path_options_test() {
+        let i = parse("193\n115\n911").unwrap();
+        let cost = |options: PathOptions| i.lowest_risk_path(&options).map(|p| p.cost);
+        assert_eq!(Some(4), cost(PathOptions::default()));
+        assert_eq!(
+            Some(2),
+            cost(PathOptions {
+                moves: Moves::WithDiagonals,
+                ..PathOptions::default()
+            })
+        );
+        assert_eq!(
+            Some(0),
+            cost(PathOptions {
+                cost: Cost::Difference,
+                ..PathOptions::default()
+            })
+        );
+        assert_eq!(
+            Some(16),
+            cost(PathOptions {
+                from: Some(Coords { row: 0, col: 2 }),
+                to: Some(Coords { row: 2, col: 0 }),
+                ..PathOptions::default()
+            })
+        );
+        // Every step costs the same, so this is the fewest steps
+        assert_eq!(
+            Some(4),
+            cost(PathOptions {
+                cost: Cost::Custom(|_, _| 1),
+                ..PathOptions::default()
+            })
+        );
+        assert_eq!(
+            None,
+            cost(PathOptions {
+                to: Some(Coords { row: 3, col: 0 }),
+                ..PathOptions::default()
+            })
+        );
+    }
+
+    #[test]
+    fn render_path_test() {
+        let i = parse("193\n115\n911").unwrap();
+        let path = i.lowest_risk_path_to_end().unwrap();
+        assert_eq!("1..\n11.\n.11", i.render_path(&path.nodes).to_string());
+        assert_eq!(
+            "\x1b[1m1\x1b[0m93\n\x1b[1m1\x1b[0m\x1b[1m1\x1b[0m5\n9\x1b[1m1\x1b[0m\x1b[1m1\x1b[0m",
+            format!("{:#}", i.render_path(&path.nodes))
+        );
     }
 
     #[test]
@@ -261,7 +289,7 @@ mod tests {
         let i = parse(TEST_INPUT).unwrap();
         assert_eq!(
             Input {
-                rows: vec![
+                risks: Grid::from_rows(vec![
                     vec![1, 1, 6, 3, 7, 5, 1, 7, 4, 2],
                     vec![1, 3, 8, 1, 3, 7, 3, 6, 7, 2],
                     vec![2, 1, 3, 6, 5, 1, 1, 3, 2, 8],
@@ -272,87 +300,10 @@ mod tests {
                     vec![3, 1, 2, 5, 4, 2, 1, 6, 3, 9],
                     vec![1, 2, 9, 3, 1, 3, 8, 5, 2, 1],
                     vec![2, 3, 1, 1, 9, 4, 4, 5, 8, 1],
-                ],
-                max_coords: Coords { row: 9, col: 9 },
+                ])
+                .unwrap(),
             },
             i
         )
     }
-
-    #[test]
-    fn adjacent_points_test() {
-        let i = parse(TEST_INPUT).unwrap();
-        // top row
-        let r1 = i.adjacents(&Coords { row: 0, col: 0 });
-        assert_eq!(
-            vec![Coords { row: 0, col: 1 }, Coords { row: 1, col: 0 }],
-            r1
-        );
-        let r2 = i.adjacents(&Coords { row: 0, col: 2 });
-        assert_eq!(
-            vec![
-                Coords { row: 0, col: 1 },
-                Coords { row: 0, col: 3 },
-                Coords { row: 1, col: 2 },
-            ],
-            r2
-        );
-        let r3 = i.adjacents(&Coords { row: 0, col: 9 });
-        assert_eq!(
-            vec![Coords { row: 0, col: 8 }, Coords { row: 1, col: 9 }],
-            r3
-        );
-        // bottom row
-        let r4 = i.adjacents(&Coords { row: 9, col: 0 });
-        assert_eq!(
-            vec![Coords { row: 8, col: 0 }, Coords { row: 9, col: 1 }],
-            r4
-        );
-        let r5 = i.adjacents(&Coords { row: 9, col: 5 });
-        assert_eq!(
-            vec![
-                Coords { row: 8, col: 5 },
-                Coords { row: 9, col: 4 },
-                Coords { row: 9, col: 6 },
-            ],
-            r5
-        );
-        let r6 = i.adjacents(&Coords { row: 9, col: 9 });
-        assert_eq!(
-            vec![Coords { row: 8, col: 9 }, Coords { row: 9, col: 8 }],
-            r6
-        );
-        // middle row
-        let r7 = i.adjacents(&Coords { row: 3, col: 0 });
-        assert_eq!(
-            vec![
-                Coords { row: 2, col: 0 },
-                Coords { row: 3, col: 1 },
-                Coords { row: 4, col: 0 },
-            ],
-            r7
-        );
-        let r8 = i.adjacents(&Coords { row: 1, col: 3 });
-        assert_eq!(
-            vec![
-                Coords { row: 0, col: 3 },
-                Coords { row: 1, col: 2 },
-                Coords { row: 1, col: 4 },
-                Coords { row: 2, col: 3 },
-            ],
-            r8
-        );
-        let r9 = i.adjacents(&Coords { row: 2, col: 9 });
-        assert_eq!(
-            vec![
-                Coords { row: 1, col: 9 },
-                Coords { row: 2, col: 8 },
-                Coords { row: 3, col: 9 },
-            ],
-            r9
-        );
-        // out of bounds
-        let r10 = i.adjacents(&Coords { row: 10, col: 10 });
-        assert!(r10.is_empty());
-    }
 }
@@ -1,4 +1,5 @@
 use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
 use std::iter::FromIterator;
 use std::result::Result as StdResult;
 
@@ -7,20 +8,43 @@ use combine::parser::char::*;
 use combine::*;
 use DecodedPacket::*;
 
+use crate::common::parse_all;
+use crate::error;
+use crate::solution::Solution;
+
+pub mod assembler;
+pub mod decode;
+pub mod eval;
+pub mod stream;
+
 pub const INPUT: &str = include_str!("../data/day_16_input");
 
-pub fn run() -> Result<()> {
-    println!("*** Day 16: Packet Decoder ***");
-    println!("Input: {}", INPUT);
-    let input = parse(INPUT)?;
-    let decoded = input.packet.decode()?;
-    let sol_1 = decoded.version_sum();
-    println!("Solution 1: {:?}", sol_1);
+const HEADER_BIT_COUNT: usize = 6;
+const LITERAL_PACKET_TYPE: usize = 4;
 
-    let sol_2 = decoded.run();
-    println!("Solution 2: {:?}", sol_2);
+pub struct Day16;
 
-    Ok(())
+impl Solution for Day16 {
+    const DAY: usize = 16;
+    const TITLE: &'static str = "Packet Decoder";
+    const INPUT: &'static str = INPUT;
+
+    type Input = DecodedPacket;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(s: &str) -> Result<Self::Input> {
+        let input = parse(s).map_err(|e| e.on_day(Self::DAY))?;
+        input.packet.decode()
+    }
+
+    fn part_1(decoded: &Self::Input) -> Result<Self::Part1> {
+        Ok(decoded.version_sum())
+    }
+
+    fn part_2(decoded: &Self::Input) -> Result<Self::Part2> {
+        decoded.run()
+    }
 }
 
 #[derive(Debug, Eq, PartialEq)]
@@ -94,291 +118,348 @@ impl DecodedPacket {
         }
     }
 
+    /// What the packet evaluates to. See [DecodedPacket::run_checked] for a typed error.
     pub fn run(&self) -> Result<usize> {
-        fn run_many(packet_type: usize, packets: &[DecodedPacket]) -> Result<usize> {
-            match packet_type {
-                0 => {
-                    let mut acc = 0;
-                    for packet in packets {
-                        acc += packet.run()?;
-                    }
-                    Ok(acc)
-                }
-                1 => {
-                    let mut acc = 1;
-                    for packet in packets {
-                        acc *= packet.run()?;
-                    }
-                    Ok(acc)
-                }
-                2 => {
-                    let mut lowest_result = None;
-                    for packet in packets {
-                        let r = packet.run()?;
-                        match lowest_result {
-                            Some(curr) if curr < r => (),
-                            _ => lowest_result = Some(r),
-                        }
-                    }
-                    lowest_result.context("No packets exist")
+        Ok(self.run_checked()?)
+    }
+
+    /// Like [DecodedPacket::run], but keeping the result of every packet along the way, in the
+    /// order they were worked out (sub-packets before the operators using them)
+    pub fn trace(&self) -> Result<Trace> {
+        fn trace_into(
+            packet: &DecodedPacket,
+            depth: usize,
+            steps: &mut Vec<TraceStep>,
+        ) -> Result<usize> {
+            let result = match packet {
+                Literal { number, .. } => *number,
+                End => bail!("No result for end..."),
+                operator => {
+                    let (packet_type, sub_packets) = operator.operator_parts()?;
+                    let results = sub_packets
+                        .iter()
+                        .map(|p| trace_into(p, depth + 1, steps))
+                        .collect::<Result<Vec<_>>>()?;
+                    eval::apply(packet_type, &results)
+                        .with_context(|| format!("Failed to evaluate {}", packet))?
                 }
-                3 => {
-                    let mut lowest_result = None;
-                    for packet in packets {
-                        let r = packet.run()?;
-                        match lowest_result {
-                            Some(curr) if curr > r => (),
-                            _ => lowest_result = Some(r),
+            };
+            steps.push(TraceStep {
+                depth,
+                expression: packet.to_string(),
+                result,
+            });
+            Ok(result)
+        }
+
+        let mut steps = vec![];
+        trace_into(self, 0, &mut steps)?;
+        Ok(Trace(steps))
+    }
+
+    /// Infix rendering, e.g. `1 + max(3, 4)`. [Display] gives an S-expression instead.
+    pub fn to_infix(&self) -> String {
+        fn infix(packet: &DecodedPacket, nested: bool) -> String {
+            match packet {
+                Literal { number, .. } => number.to_string(),
+                End => "end".to_string(),
+                operator => match operator.operator_parts() {
+                    Ok((packet_type, sub_packets)) => {
+                        let operands: Vec<String> =
+                            sub_packets.iter().map(|p| infix(p, true)).collect();
+                        match infix_symbol(packet_type) {
+                            Some(symbol) if nested => {
+                                format!("({})", operands.join(&format!(" {} ", symbol)))
+                            }
+                            Some(symbol) => operands.join(&format!(" {} ", symbol)),
+                            None => {
+                                format!("{}({})", operator_name(packet_type), operands.join(", "))
+                            }
                         }
                     }
-                    lowest_result.context("No packets exist")
-                }
-                5 => match (packets.get(0), packets.get(1)) {
-                    (Some(first), Some(second)) => {
-                        let r = if first.run()? > second.run()? { 1 } else { 0 };
-                        Ok(r)
-                    }
-                    _ => bail!(
-                        "Did not have 2 sub-packets for packet type [{}]",
-                        packet_type
-                    ),
+                    Err(_) => "end".to_string(),
                 },
-                6 => match (packets.get(0), packets.get(1)) {
-                    (Some(first), Some(second)) => {
-                        let r = if first.run()? < second.run()? { 1 } else { 0 };
-                        Ok(r)
-                    }
-                    _ => bail!(
-                        "Did not have 2 sub-packets for packet type [{}]",
-                        packet_type
-                    ),
-                },
-                7 => match (packets.get(0), packets.get(1)) {
-                    (Some(first), Some(second)) => {
-                        let r = if first.run()? == second.run()? { 1 } else { 0 };
-                        Ok(r)
-                    }
-                    _ => bail!(
-                        "Did not have 2 sub-packets for packet type [{}]",
-                        packet_type
-                    ),
-                },
-                other => bail!("Unsupported packet type [{}]", other),
             }
         }
+        infix(self, false)
+    }
 
+    // Operators are the same whatever their length type
+    fn operator_parts(&self) -> Result<(usize, &[DecodedPacket])> {
         match self {
-            Literal { number, .. } => Ok(*number),
             OperatorType0 {
                 packet_type,
                 sub_packets,
                 ..
-            } => run_many(*packet_type, sub_packets),
+            } => Ok((*packet_type, sub_packets)),
             OperatorType1 {
                 packet_type,
                 sub_packets,
                 ..
-            } => run_many(*packet_type, sub_packets),
-            End => bail!("No result for end..."),
+            } => Ok((*packet_type, sub_packets)),
+            other => bail!("Not an operator packet: {:?}", other),
         }
     }
 }
 
-impl Packet {
-    pub fn decode(&self) -> Result<DecodedPacket> {
-        Self::decode_bits(&self.0)
-    }
-
-    fn decode_bits(bits: &[u8]) -> Result<DecodedPacket> {
-        let packet_length = bits.len();
-
-        // keep skipping bits until we reach something that can be interpreted as headers
-
-        for skipped_bits in 0..packet_length {
-            let packet = bits.get(skipped_bits..).context(format!(
-                "No packets could be retrieved from offset [{}]",
-                skipped_bits
-            ))?;
-            let version_bits = &packet
-                .get(0..3)
-                .context("Could not retrieve version bits")?;
-            let type_bits = &packet
-                .get(3..6)
-                .context("Could not retrieve packet type bits")?;
-            let header_bit_count = 6;
-
-            let packet_version_result = to_decimal(version_bits);
-            let packet_type_result = to_decimal(type_bits);
-
-            if packet_type_result.is_ok() && packet_version_result.is_ok() {
-                let packet_version = to_decimal(version_bits)?;
-                let packet_type = to_decimal(type_bits)?;
-                match packet_type {
-                    4 => {
-                        // Literal
-                        let mut bits_buffer = vec![];
-                        for c in packet
-                            .get(6..)
-                            .context("Could not get number bits")?
-                            .chunks_exact(5)
-                        {
-                            bits_buffer.extend_from_slice(&c[1..]);
-                            if c[0] == 0 {
-                                break;
-                            }
-                        }
-                        let bits_from_packet_used =
-                            header_bit_count + (bits_buffer.len() / 4 * 5) + skipped_bits;
-                        let number = to_decimal(bits_buffer.as_slice())?;
-                        return Ok(Literal {
-                            packet_version,
-                            packet_type,
-                            number,
-                            bits_from_packet_used,
-                        });
-                    }
-                    _ => {
-                        // Operator
-                        let length_type = &packet
-                            .get(6)
-                            .context("Could not retrieve length type bit for operator")?;
-                        let bits_used_so_far = skipped_bits + header_bit_count + 1;
-
-                        fn decode_inner_bits<F>(
-                            sub_packet_bits: &[u8],
-                            exit_loop_check: F,
-                        ) -> Result<(Vec<DecodedPacket>, usize)>
-                        where
-                            F: Fn(&[DecodedPacket]) -> bool,
-                        {
-                            let mut sub_packets = vec![];
-                            let mut sub_packet_skip = 0;
-
-                            loop {
-                                let inner_packet_bits =
-                                    sub_packet_bits.get(sub_packet_skip..).context(format!(
-                                        "Could not get sub packet bits from offset [{}]",
-                                        sub_packet_skip
-                                    ))?;
-
-                                let decoded_inner_packet = Packet::decode_bits(inner_packet_bits)?;
-
-                                match decoded_inner_packet {
-                                    DecodedPacket::Literal {
-                                        bits_from_packet_used,
-                                        ..
-                                    } => {
-                                        sub_packet_skip += bits_from_packet_used;
-                                        sub_packets.push(decoded_inner_packet);
-                                    }
-                                    DecodedPacket::OperatorType0 {
-                                        bits_from_packet_used,
-                                        ..
-                                    } => {
-                                        sub_packet_skip += bits_from_packet_used;
-                                        sub_packets.push(decoded_inner_packet);
-                                    }
-                                    DecodedPacket::OperatorType1 {
-                                        bits_from_packet_used,
-                                        ..
-                                    } => {
-                                        sub_packet_skip += bits_from_packet_used;
-                                        sub_packets.push(decoded_inner_packet);
-                                    }
-                                    DecodedPacket::End => {
-                                        break;
-                                    }
-                                }
+impl DecodedPacket {
+    /// A literal packet, as it would be decoded from the shortest encoding of `number`
+    pub fn literal(packet_version: usize, number: usize) -> DecodedPacket {
+        Literal {
+            packet_version,
+            packet_type: LITERAL_PACKET_TYPE,
+            number,
+            bits_from_packet_used: HEADER_BIT_COUNT + literal_groups(number) * 5,
+        }
+    }
 
-                                if exit_loop_check(&sub_packets) {
-                                    break;
-                                }
-                            }
+    /// An operator packet whose sub-packets are given by their total length in bits
+    pub fn operator_type_0(
+        packet_version: usize,
+        packet_type: usize,
+        sub_packets: Vec<DecodedPacket>,
+    ) -> DecodedPacket {
+        let sub_packet_bit_length = sub_packets.iter().map(|p| p.bits_from_packet_used()).sum();
+        OperatorType0 {
+            packet_version,
+            packet_type,
+            sub_packet_bit_length,
+            bits_from_packet_used: HEADER_BIT_COUNT + 1 + 15 + sub_packet_bit_length,
+            sub_packets,
+        }
+    }
 
-                            Ok((sub_packets, sub_packet_skip))
-                        }
+    /// An operator packet whose sub-packets are given by how many there are
+    pub fn operator_type_1(
+        packet_version: usize,
+        packet_type: usize,
+        sub_packets: Vec<DecodedPacket>,
+    ) -> DecodedPacket {
+        let sub_packet_bits: usize = sub_packets.iter().map(|p| p.bits_from_packet_used()).sum();
+        OperatorType1 {
+            packet_version,
+            packet_type,
+            sub_packet_count: sub_packets.len(),
+            bits_from_packet_used: HEADER_BIT_COUNT + 1 + 11 + sub_packet_bits,
+            sub_packets,
+        }
+    }
 
-                        match length_type {
-                            0 => {
-                                let length_bits = &packet.get(7..22).context(
-                                    "Format could not get length type bits for OpType 0",
-                                )?;
-                                let sub_packet_bit_length = to_decimal(length_bits)?;
-
-                                let sub_packet_bits =
-                                    &packet
-                                        .get(22..22 + sub_packet_bit_length)
-                                        .context("Could not get sub packet bits for OptType 0")?;
-                                let bits_from_packet_used =
-                                    bits_used_so_far + 15 + sub_packet_bit_length;
-
-                                let (sub_packets, _) =
-                                    decode_inner_bits(sub_packet_bits, |_| false)?;
-                                return Ok(OperatorType0 {
-                                    packet_version,
-                                    packet_type,
-                                    sub_packet_bit_length,
-                                    bits_from_packet_used,
-                                    sub_packets,
-                                });
-                            }
-                            1 => {
-                                let sub_packet_count_bits = packet
-                                    .get(7..18)
-                                    .context("Could not get sub packet count bits for OpType 1")?;
-                                let sub_packet_count = to_decimal(sub_packet_count_bits)?;
-
-                                let op_type_1_bits_used_so_far = bits_used_so_far + 11;
-                                let sub_packet_bits = packet
-                                    .get(op_type_1_bits_used_so_far..)
-                                    .context("Could not get sub packet bits for OpType 1")?;
-
-                                let (sub_packets, sub_packet_skip) =
-                                    decode_inner_bits(sub_packet_bits, |sub_packets| {
-                                        sub_packets.len() == sub_packet_count
-                                    })?;
-
-                                let bits_from_packet_used =
-                                    op_type_1_bits_used_so_far + sub_packet_skip;
-
-                                return Ok(OperatorType1 {
-                                    packet_version,
-                                    packet_type,
-                                    sub_packet_count,
-                                    bits_from_packet_used,
-                                    sub_packets,
-                                });
-                            }
-                            other => bail!("Unsupported packet type [{}]", other),
-                        }
+    pub fn bits_from_packet_used(&self) -> usize {
+        match self {
+            Literal {
+                bits_from_packet_used,
+                ..
+            } => *bits_from_packet_used,
+            OperatorType0 {
+                bits_from_packet_used,
+                ..
+            } => *bits_from_packet_used,
+            OperatorType1 {
+                bits_from_packet_used,
+                ..
+            } => *bits_from_packet_used,
+            End => 0,
+        }
+    }
+
+    /// Turns the packet back into a transmission, which [Packet::decode]s into this packet again.
+    ///
+    /// Operators keep their length type, and literals keep any leading zero groups they were
+    /// decoded with.
+    pub fn encode(&self) -> Result<Packet> {
+        let mut bits = vec![];
+        self.encode_into(&mut bits)?;
+        Ok(Packet(bits))
+    }
+
+    fn encode_into(&self, bits: &mut Vec<u8>) -> Result<()> {
+        match self {
+            Literal {
+                packet_version,
+                packet_type,
+                number,
+                bits_from_packet_used,
+            } => {
+                if *packet_type != LITERAL_PACKET_TYPE {
+                    bail!("Literal has non-literal packet type [{}]", packet_type)
+                }
+                push_bits(bits, *packet_version, 3)?;
+                push_bits(bits, *packet_type, 3)?;
+                let groups = literal_groups(*number)
+                    .max(bits_from_packet_used.saturating_sub(HEADER_BIT_COUNT) / 5);
+                for group_idx in (0..groups).rev() {
+                    let is_last_group = group_idx == 0;
+                    bits.push(if is_last_group { 0 } else { 1 });
+                    let group = number.checked_shr(group_idx as u32 * 4).unwrap_or(0) & 0b1111;
+                    push_bits(bits, group, 4)?;
+                }
+            }
+            OperatorType0 {
+                packet_version,
+                packet_type,
+                sub_packets,
+                ..
+            } => {
+                push_operator_header(bits, *packet_version, *packet_type)?;
+                bits.push(0);
+                let mut sub_packet_bits = vec![];
+                for sub_packet in sub_packets {
+                    sub_packet.encode_into(&mut sub_packet_bits)?;
+                }
+                push_bits(bits, sub_packet_bits.len(), 15)?;
+                bits.extend(sub_packet_bits);
+            }
+            OperatorType1 {
+                packet_version,
+                packet_type,
+                sub_packets,
+                ..
+            } => {
+                push_operator_header(bits, *packet_version, *packet_type)?;
+                bits.push(1);
+                push_bits(bits, sub_packets.len(), 11)?;
+                for sub_packet in sub_packets {
+                    sub_packet.encode_into(bits)?;
+                }
+            }
+            End => bail!("End is not a packet, so can't be encoded"),
+        }
+        Ok(())
+    }
+}
+
+/// S-expression rendering, e.g. `(sum 1 (max 3 4))`
+impl Display for DecodedPacket {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Literal { number, .. } => write!(f, "{}", number),
+            End => write!(f, "end"),
+            operator => {
+                if let Ok((packet_type, sub_packets)) = operator.operator_parts() {
+                    write!(f, "({}", operator_name(packet_type))?;
+                    for sub_packet in sub_packets {
+                        write!(f, " {}", sub_packet)?;
                     }
+                    write!(f, ")")?;
                 }
-            } else {
-                continue;
+                Ok(())
             }
         }
+    }
+}
 
-        Ok(End)
+/// Results of evaluating each packet in a tree, from [DecodedPacket::trace]
+#[derive(Debug, Eq, PartialEq)]
+pub struct Trace(pub Vec<TraceStep>);
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct TraceStep {
+    /// How deeply nested the packet is; the outermost is 0
+    pub depth: usize,
+    pub expression: String,
+    pub result: usize,
+}
+
+impl Trace {
+    pub fn result(&self) -> Option<usize> {
+        self.0.last().map(|step| step.result)
     }
 }
 
-fn to_decimal(bits: &[u8]) -> Result<usize> {
-    let mut acc = 0;
-    for (idx, next) in bits.iter().rev().enumerate() {
-        if *next == 1 || *next == 0 {
-            acc += *next as usize * (2usize.pow(idx as u32));
-        } else {
-            bail!(
-                "Nope, invalid bit [{}] at position [{}] from right",
-                next,
-                idx
-            )
+impl Display for Trace {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (idx, step) in self.0.iter().enumerate() {
+            if idx > 0 {
+                writeln!(f)?;
+            }
+            write!(
+                f,
+                "{}{} = {}",
+                "  ".repeat(step.depth),
+                step.expression,
+                step.result
+            )?;
         }
+        Ok(())
+    }
+}
+
+fn operator_name(packet_type: usize) -> String {
+    match packet_type {
+        0 => "sum".to_string(),
+        1 => "product".to_string(),
+        2 => "min".to_string(),
+        3 => "max".to_string(),
+        5 => "gt".to_string(),
+        6 => "lt".to_string(),
+        7 => "eq".to_string(),
+        other => format!("op{}", other),
+    }
+}
+
+fn infix_symbol(packet_type: usize) -> Option<&'static str> {
+    match packet_type {
+        0 => Some("+"),
+        1 => Some("*"),
+        5 => Some(">"),
+        6 => Some("<"),
+        7 => Some("=="),
+        _ => None,
     }
-    Ok(acc)
 }
 
-pub fn parse(s: &str) -> StdResult<Input, easy::ParseError<&str>> {
+impl Packet {
+    pub fn bits(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// The transmission as hex, padded with 0 bits to a whole number of hex digits
+    pub fn to_hex(&self) -> String {
+        self.0
+            .chunks(4)
+            .map(|chunk| {
+                let nibble = (0..4).fold(0, |acc, idx| {
+                    (acc << 1) | chunk.get(idx).copied().unwrap_or(0)
+                });
+                std::char::from_digit(nibble as u32, 16)
+                    .unwrap_or('0')
+                    .to_ascii_uppercase()
+            })
+            .collect()
+    }
+}
+
+// How many 4 bit groups it takes to write out `number`
+fn literal_groups(number: usize) -> usize {
+    let significant_bits = (usize::BITS - number.leading_zeros()) as usize;
+    significant_bits.div_ceil(4).max(1)
+}
+
+fn push_operator_header(
+    bits: &mut Vec<u8>,
+    packet_version: usize,
+    packet_type: usize,
+) -> Result<()> {
+    if packet_type == LITERAL_PACKET_TYPE {
+        bail!("Operator has the literal packet type [{}]", packet_type)
+    }
+    push_bits(bits, packet_version, 3)?;
+    push_bits(bits, packet_type, 3)
+}
+
+// Big-endian, as the decoder reads them
+fn push_bits(bits: &mut Vec<u8>, value: usize, width: usize) -> Result<()> {
+    if width < usize::BITS as usize && value >> width != 0 {
+        bail!("[{}] does not fit in [{}] bits", value, width)
+    }
+    for idx in (0..width).rev() {
+        bits.push(((value >> idx) & 1) as u8);
+    }
+    Ok(())
+}
+
+pub fn parse(s: &str) -> StdResult<Input, error::ParseError> {
     let mapper: HashMap<char, [u8; 4]> = HashMap::from_iter([
         ('0', [0, 0, 0, 0]),
         ('1', [0, 0, 0, 1]),
@@ -398,7 +479,7 @@ pub fn parse(s: &str) -> StdResult<Input, easy::ParseError<&str>> {
         ('F', [1, 1, 1, 1]),
     ]);
 
-    let mut parser = many1(upper().or(digit())).map(|raw: Vec<char>| {
+    let parser = many1(upper().or(digit())).map(|raw: Vec<char>| {
         let expanded: Vec<u8> = raw
             .iter()
             .filter_map(|c| mapper.get(c).copied())
@@ -407,12 +488,13 @@ pub fn parse(s: &str) -> StdResult<Input, easy::ParseError<&str>> {
         let packet = Packet(expanded);
         Input { raw, packet }
     });
-    let (r, _) = parser.easy_parse(s)?;
-    Ok(r)
+    parse_all(parser, s)
 }
 
 #[cfg(test)]
 mod tests {
+    use proptest::prelude::*;
+
     use super::*;
 
     static TEST_INPUT_1: &str = "D2FE28";
@@ -893,4 +975,118 @@ mod tests {
         let r = i.packet.decode().unwrap().run().unwrap();
         assert_eq!(1, r)
     }
+
+    #[test]
+    fn encode_literal_test() {
+        let packet = DecodedPacket::literal(6, 2021);
+        let encoded = packet.encode().unwrap();
+        assert_eq!(
+            &[1, 1, 0, 1, 0, 0, 1, 0, 1, 1, 1, 1, 1, 1, 1, 0, 0, 0, 1, 0, 1],
+            encoded.bits()
+        );
+        assert_eq!("D2FE28", encoded.to_hex());
+    }
+
+    #[test]
+    fn encode_examples_round_trip_test() {
+        for hex in [
+            TEST_INPUT_1,
+            TEST_INPUT_OP_PACKET_1,
+            TEST_INPUT_OP_PACKET_2,
+            TEST_INPUT_OP_PACKET_3,
+            TEST_INPUT_OP_PACKET_4,
+            TEST_INPUT_OP_PACKET_5,
+            TEST_INPUT_OP_PACKET_6,
+            INPUT.trim(),
+        ] {
+            let decoded = parse(hex).unwrap().packet.decode().unwrap();
+            let encoded = decoded.encode().unwrap();
+            // Transmissions are padded out with 0s
+            assert_eq!(
+                hex.trim_end_matches('0'),
+                encoded.to_hex().trim_end_matches('0')
+            );
+            assert_eq!(decoded, encoded.decode().unwrap());
+        }
+    }
+
+    #[test]
+    fn encode_invalid_test() {
+        assert!(DecodedPacket::literal(8, 1).encode().is_err());
+        assert!(
+            DecodedPacket::operator_type_1(1, 4, vec![DecodedPacket::literal(1, 1)])
+                .encode()
+                .is_err()
+        );
+        assert!(End.encode().is_err());
+    }
+
+    fn packet_strategy() -> impl Strategy<Value = DecodedPacket> {
+        let literal = (0..8usize, proptest::arbitrary::any::<usize>())
+            .prop_map(|(packet_version, number)| DecodedPacket::literal(packet_version, number));
+        literal.prop_recursive(4, 64, 4, |inner| {
+            (
+                0..8usize,
+                prop_oneof![0..4usize, 5..8usize],
+                prop::collection::vec(inner, 1..4),
+                proptest::arbitrary::any::<bool>(),
+            )
+                .prop_map(|(packet_version, packet_type, sub_packets, by_count)| {
+                    if by_count {
+                        DecodedPacket::operator_type_1(packet_version, packet_type, sub_packets)
+                    } else {
+                        DecodedPacket::operator_type_0(packet_version, packet_type, sub_packets)
+                    }
+                })
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn encode_decode_round_trip_test(packet in packet_strategy()) {
+            let encoded = packet.encode().unwrap();
+            prop_assert_eq!(&packet, &encoded.decode().unwrap());
+            let reparsed = parse(&encoded.to_hex()).unwrap();
+            prop_assert_eq!(&packet, &reparsed.packet.decode().unwrap());
+        }
+    }
+
+    #[test]
+    fn display_test() {
+        let decoded = parse("9C0141080250320F1802104A08")
+            .unwrap()
+            .packet
+            .decode()
+            .unwrap();
+        assert_eq!("(eq (sum 1 3) (product 2 2))", decoded.to_string());
+        assert_eq!("(1 + 3) == (2 * 2)", decoded.to_infix());
+
+        let decoded = parse("880086C3E88112").unwrap().packet.decode().unwrap();
+        assert_eq!("(min 7 8 9)", decoded.to_string());
+        assert_eq!("min(7, 8, 9)", decoded.to_infix());
+    }
+
+    #[test]
+    fn trace_test() {
+        let decoded = parse("9C0141080250320F1802104A08")
+            .unwrap()
+            .packet
+            .decode()
+            .unwrap();
+        let trace = decoded.trace().unwrap();
+        assert_eq!(Some(1), trace.result());
+        assert_eq!(
+            "    1 = 1
+    3 = 3
+  (sum 1 3) = 4
+    2 = 2
+    2 = 2
+  (product 2 2) = 4
+(eq (sum 1 3) (product 2 2)) = 1",
+            trace.to_string()
+        );
+
+        let bad = DecodedPacket::operator_type_0(0, 5, vec![DecodedPacket::literal(0, 1)]);
+        assert!(bad.trace().is_err());
+    }
 }
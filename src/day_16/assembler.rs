@@ -0,0 +1,151 @@
+//! Writing transmissions by hand, as expressions like `sum(1, product(2, 3), gt(4, 5))`.
+//!
+//! Operators are named as in [DecodedPacket]'s [Display](std::fmt::Display) (`sum`, `product`,
+//! `min`, `max`, `gt`, `lt` and `eq`) and are given their sub-packets by bit length. Versions
+//! are 0 unless given after an `@`, as in `sum@3(1@5, 2)`.
+
+use std::num::ParseIntError;
+use std::result::Result as StdResult;
+
+use anyhow::Result;
+use combine::parser::char::*;
+use combine::*;
+
+use super::DecodedPacket;
+use crate::common::{parse_all, usize_parser};
+use crate::error;
+
+/// Compiles an expression into hex, ready to be given to [super::parse]
+pub fn assemble(s: &str) -> Result<String> {
+    let packet = parse_expression(s)?;
+    Ok(packet.encode()?.to_hex())
+}
+
+pub fn parse_expression(s: &str) -> StdResult<DecodedPacket, error::ParseError> {
+    parse_all(spaces().with(expression()), s)
+}
+
+parser! {
+    fn expression[Input]()(Input) -> DecodedPacket
+    where [Input: Stream<Token = char>,
+          Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+          <<Input as StreamOnce>::Error as combine::ParseError<
+              char,
+              <Input as StreamOnce>::Range,
+              <Input as StreamOnce>::Position,
+          >>::StreamError: From<ParseIntError>,
+          <Input as combine::StreamOnce>::Error: combine::ParseError<
+              char,
+              <Input as combine::StreamOnce>::Range,
+              <Input as combine::StreamOnce>::Position,
+          >]
+    {
+        expression_()
+    }
+}
+
+fn expression_<Input>() -> impl Parser<Input, Output = DecodedPacket>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+    <<Input as StreamOnce>::Error as combine::ParseError<
+        char,
+        <Input as StreamOnce>::Range,
+        <Input as StreamOnce>::Position,
+    >>::StreamError: From<ParseIntError>,
+    <Input as combine::StreamOnce>::Error: combine::ParseError<
+        char,
+        <Input as combine::StreamOnce>::Range,
+        <Input as combine::StreamOnce>::Position,
+    >,
+{
+    let lex_char = |c| char(c).skip(spaces());
+    let version = || optional(char('@').with(usize_parser())).map(|v| v.unwrap_or(0));
+
+    let literal = (usize_parser(), version())
+        .skip(spaces())
+        .map(|(number, packet_version)| DecodedPacket::literal(packet_version, number));
+
+    let operator_type = choice((
+        attempt(string("sum")).map(|_| 0),
+        attempt(string("product")).map(|_| 1),
+        attempt(string("min")).map(|_| 2),
+        attempt(string("max")).map(|_| 3),
+        attempt(string("gt")).map(|_| 5),
+        attempt(string("lt")).map(|_| 6),
+        attempt(string("eq")).map(|_| 7),
+    ));
+    let operator = (
+        operator_type,
+        version(),
+        spaces().with(lex_char('(')),
+        sep_by(expression(), lex_char(',')),
+        lex_char(')'),
+    )
+        .map(|(packet_type, packet_version, _, sub_packets, _)| {
+            DecodedPacket::operator_type_0(packet_version, packet_type, sub_packets)
+        });
+
+    choice((literal, operator))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode(hex: &str) -> DecodedPacket {
+        super::super::parse(hex).unwrap().packet.decode().unwrap()
+    }
+
+    #[test]
+    fn assemble_literal_test() {
+        // The literal from the puzzle's first example
+        assert_eq!("D2FE28", assemble("2021@6").unwrap());
+    }
+
+    #[test]
+    fn assemble_test() {
+        let decoded = decode(&assemble("eq(sum(1, 3), product(2, 2))").unwrap());
+        assert_eq!("(eq (sum 1 3) (product 2 2))", decoded.to_string());
+        assert_eq!(1, decoded.run().unwrap());
+
+        let decoded = decode(&assemble(" sum(1, product(2,3),\n  gt(4, 5)) ").unwrap());
+        assert_eq!(7, decoded.run().unwrap());
+
+        let decoded = decode(&assemble("max@1(min@2(5), 3@4)").unwrap());
+        assert_eq!("(max (min 5) 3)", decoded.to_string());
+        assert_eq!(7, decoded.version_sum());
+        assert_eq!(5, decoded.run().unwrap());
+    }
+
+    #[test]
+    fn parse_expression_test() {
+        let r = parse_expression("min(7, 8, 9)").unwrap();
+        assert_eq!(
+            DecodedPacket::operator_type_0(
+                0,
+                2,
+                vec![
+                    DecodedPacket::literal(0, 7),
+                    DecodedPacket::literal(0, 8),
+                    DecodedPacket::literal(0, 9)
+                ]
+            ),
+            r
+        );
+        // Sub-packets back to back, in the same order
+        assert_eq!(
+            decode("C200B40A82").to_infix(),
+            parse_expression("sum(1, 2)").unwrap().to_infix()
+        );
+    }
+
+    #[test]
+    fn parse_expression_error_test() {
+        let r = parse_expression("sum(1, nope)").unwrap_err();
+        assert_eq!(1, r.line);
+        assert_eq!(8, r.column);
+        assert!(parse_expression("sum(1, 2").is_err());
+        assert!(parse_expression("sum(1) 2").is_err());
+    }
+}
@@ -0,0 +1,382 @@
+//! Turning a [Packet]'s bits into a [DecodedPacket], saying exactly where and why that fails.
+
+use std::fmt::{Display, Formatter};
+use std::io;
+use std::result::Result as StdResult;
+
+use anyhow::Result;
+
+use super::DecodedPacket::{self, *};
+use super::{Packet, LITERAL_PACKET_TYPE};
+
+/// Why decoding failed, and where
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct DecodeError {
+    /// Bits into the transmission
+    pub offset: usize,
+    /// Index of the sub-packet taken at each level to get from the outermost packet to the one
+    /// being decoded; empty if it was the outermost packet
+    pub path: Vec<usize>,
+    pub kind: DecodeErrorKind,
+}
+
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum DecodeErrorKind {
+    /// The transmission ended
+    Truncated { expected: Expected },
+    /// Reading on would go past the bit length given by the operator around the packet, which
+    /// ends at `end`
+    PastSubPackets { expected: Expected, end: usize },
+    /// A literal with more groups than a `usize` can hold
+    LiteralTooWide,
+    /// Bits after the outermost packet that aren't 0. Only reported by [Packet::decode_lenient].
+    NonZeroPadding,
+    /// A char that isn't hex. Only reported by [super::stream].
+    InvalidHex(char),
+    /// The transmission couldn't be read. Only reported by [super::stream].
+    Unreadable(io::ErrorKind),
+}
+
+/// What was being read
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum Expected {
+    Header,
+    LengthType,
+    SubPacketBitLength,
+    SubPacketCount,
+    LiteralGroup,
+}
+
+/// As much of a transmission as could be decoded, from [Packet::decode_lenient]
+#[derive(Debug, Eq, PartialEq)]
+pub struct LenientDecode {
+    /// None if not even the outermost packet's header and length could be read. Operators that
+    /// failed part way only have the sub-packets decoded before that, and literals only the
+    /// groups read.
+    pub packet: Option<DecodedPacket>,
+    pub diagnostics: Vec<DecodeError>,
+}
+
+impl Display for DecodeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.kind {
+            DecodeErrorKind::Truncated { expected } => write!(
+                f,
+                "Expected {} at bit [{}] in packet path {:?}, but the transmission ended",
+                expected, self.offset, self.path
+            ),
+            DecodeErrorKind::PastSubPackets { expected, end } => write!(
+                f,
+                "Expected {} at bit [{}] in packet path {:?}, but its operator's sub-packets end at bit [{}]",
+                expected, self.offset, self.path, end
+            ),
+            DecodeErrorKind::LiteralTooWide => write!(
+                f,
+                "Literal at bit [{}] in packet path {:?} does not fit in a usize; try stream::events",
+                self.offset, self.path
+            ),
+            DecodeErrorKind::NonZeroPadding => write!(
+                f,
+                "Padding after the outermost packet has a 1 at bit [{}]",
+                self.offset
+            ),
+            DecodeErrorKind::InvalidHex(c) => write!(
+                f,
+                "Invalid hex [{}] at bit [{}] in packet path {:?}",
+                c, self.offset, self.path
+            ),
+            DecodeErrorKind::Unreadable(kind) => write!(
+                f,
+                "Could not read bit [{}] in packet path {:?}: {}",
+                self.offset, self.path, kind
+            ),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+impl Display for Expected {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            Expected::Header => "a packet header",
+            Expected::LengthType => "a length type",
+            Expected::SubPacketBitLength => "a sub-packet bit length",
+            Expected::SubPacketCount => "a sub-packet count",
+            Expected::LiteralGroup => "a literal group",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+impl Packet {
+    pub fn decode(&self) -> Result<DecodedPacket> {
+        Ok(self.decode_checked()?)
+    }
+
+    /// Like [Packet::decode], but with a typed error. Padding after the outermost packet is
+    /// ignored.
+    pub fn decode_checked(&self) -> StdResult<DecodedPacket, DecodeError> {
+        let mut decoder = Decoder::new(&self.0);
+        let packet = decoder.packet();
+        match (packet, decoder.failure) {
+            (_, Some(failure)) => Err(failure),
+            (Some(packet), None) => Ok(packet),
+            // Only a failure should stop a packet being decoded, but say where it stopped anyway
+            (None, None) => Err(DecodeError {
+                offset: decoder.offset,
+                path: vec![],
+                kind: DecodeErrorKind::Truncated {
+                    expected: Expected::Header,
+                },
+            }),
+        }
+    }
+
+    /// Decodes what it can, stopping at the first failure, and also checks that the padding
+    /// after the outermost packet is all 0s
+    pub fn decode_lenient(&self) -> LenientDecode {
+        let mut decoder = Decoder::new(&self.0);
+        let packet = decoder.packet();
+        let mut diagnostics: Vec<_> = decoder.failure.into_iter().collect();
+        if diagnostics.is_empty() {
+            let padding_offset = decoder.offset;
+            if let Some(idx) = self.0[padding_offset..].iter().position(|b| *b != 0) {
+                diagnostics.push(DecodeError {
+                    offset: padding_offset + idx,
+                    path: vec![],
+                    kind: DecodeErrorKind::NonZeroPadding,
+                });
+            }
+        }
+        LenientDecode {
+            packet,
+            diagnostics,
+        }
+    }
+}
+
+struct Decoder<'a> {
+    bits: &'a [u8],
+    offset: usize,
+    path: Vec<usize>,
+    // Where the sub-packets of the type 0 operators being decoded end, innermost last. Reads
+    // can't go past any of them, as an operator's length may claim more than its parent allows.
+    sub_packet_ends: Vec<usize>,
+    failure: Option<DecodeError>,
+}
+
+impl<'a> Decoder<'a> {
+    fn new(bits: &'a [u8]) -> Decoder<'a> {
+        Decoder {
+            bits,
+            offset: 0,
+            path: vec![],
+            sub_packet_ends: vec![],
+            failure: None,
+        }
+    }
+
+    fn fail(&mut self, offset: usize, kind: DecodeErrorKind) {
+        self.failure = Some(DecodeError {
+            offset,
+            path: self.path.clone(),
+            kind,
+        });
+    }
+
+    // None once anything has failed, so that decoding stops there
+    fn read(&mut self, width: usize, expected: Expected) -> Option<usize> {
+        if self.failure.is_some() {
+            return None;
+        }
+        let end = self.offset + width;
+        match self.sub_packet_ends.iter().min() {
+            Some(sub_packets_end) if end > *sub_packets_end => {
+                let kind = DecodeErrorKind::PastSubPackets {
+                    expected,
+                    end: *sub_packets_end,
+                };
+                self.fail(self.offset, kind);
+                return None;
+            }
+            _ if end > self.bits.len() => {
+                self.fail(self.offset, DecodeErrorKind::Truncated { expected });
+                return None;
+            }
+            _ => (),
+        }
+        let value = self.bits[self.offset..end]
+            .iter()
+            .fold(0, |acc, bit| (acc << 1) | *bit as usize);
+        self.offset = end;
+        Some(value)
+    }
+
+    fn packet(&mut self) -> Option<DecodedPacket> {
+        let start_offset = self.offset;
+        let header = self.read(6, Expected::Header)?;
+        let (packet_version, packet_type) = (header >> 3, header & 0b111);
+        if packet_type == LITERAL_PACKET_TYPE {
+            let mut number: usize = 0;
+            let mut groups = 0;
+            while let Some(group) = self.read(5, Expected::LiteralGroup) {
+                match number.checked_mul(16) {
+                    Some(shifted) => number = shifted | (group & 0b1111),
+                    None => {
+                        self.fail(self.offset - 5, DecodeErrorKind::LiteralTooWide);
+                        break;
+                    }
+                }
+                groups += 1;
+                let is_last_group = group >> 4 == 0;
+                if is_last_group {
+                    break;
+                }
+            }
+            if groups == 0 {
+                return None;
+            }
+            return Some(Literal {
+                packet_version,
+                packet_type,
+                number,
+                bits_from_packet_used: self.offset - start_offset,
+            });
+        }
+
+        let mut sub_packets = vec![];
+        if self.read(1, Expected::LengthType)? == 0 {
+            let sub_packet_bit_length = self.read(15, Expected::SubPacketBitLength)?;
+            let end = self.offset + sub_packet_bit_length;
+            self.sub_packet_ends.push(end);
+            while self.offset < end && self.failure.is_none() {
+                self.sub_packet(&mut sub_packets);
+            }
+            self.sub_packet_ends.pop();
+            Some(OperatorType0 {
+                packet_version,
+                packet_type,
+                sub_packet_bit_length,
+                bits_from_packet_used: self.offset - start_offset,
+                sub_packets,
+            })
+        } else {
+            let sub_packet_count = self.read(11, Expected::SubPacketCount)?;
+            while sub_packets.len() < sub_packet_count && self.failure.is_none() {
+                self.sub_packet(&mut sub_packets);
+            }
+            Some(OperatorType1 {
+                packet_version,
+                packet_type,
+                sub_packet_count,
+                bits_from_packet_used: self.offset - start_offset,
+                sub_packets,
+            })
+        }
+    }
+
+    fn sub_packet(&mut self, sub_packets: &mut Vec<DecodedPacket>) {
+        self.path.push(sub_packets.len());
+        let sub_packet = self.packet();
+        self.path.pop();
+        sub_packets.extend(sub_packet);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn packet(hex: &str) -> Packet {
+        super::super::parse(hex).unwrap().packet
+    }
+
+    #[test]
+    fn decode_checked_test() {
+        // The operator says its sub-packets take 27 bits, but the transmission stops short
+        let r = packet("38006F4529").decode_checked();
+        assert_eq!(
+            Err(DecodeError {
+                offset: 39,
+                path: vec![1],
+                kind: DecodeErrorKind::Truncated {
+                    expected: Expected::LiteralGroup
+                }
+            }),
+            r
+        );
+
+        let r = packet("0").decode_checked().unwrap_err();
+        assert_eq!(
+            "Expected a packet header at bit [0] in packet path [], but the transmission ended",
+            r.to_string()
+        );
+    }
+
+    #[test]
+    fn decode_checked_past_sub_packets_test() {
+        // A sum whose sub-packets should take 10 bits, but the literal in it takes 11
+        let mut bits = vec![0, 0, 0, 0, 0, 0, 0];
+        bits.extend(std::iter::repeat_n(0, 11));
+        bits.extend([1, 0, 1, 0]);
+        bits.extend([0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 1]);
+        let r = Packet(bits).decode_checked();
+        assert_eq!(
+            Err(DecodeError {
+                offset: 28,
+                path: vec![0],
+                kind: DecodeErrorKind::PastSubPackets {
+                    expected: Expected::LiteralGroup,
+                    end: 32
+                }
+            }),
+            r
+        );
+    }
+
+    #[test]
+    fn decode_checked_nested_past_sub_packets_test() {
+        // The outer sum's sub-packets end at bit 44, but the sum inside it claims theirs run to 55
+        let r = packet("0000580000B102").decode_checked();
+        assert_eq!(
+            Err(DecodeError {
+                offset: 44,
+                path: vec![0, 0],
+                kind: DecodeErrorKind::PastSubPackets {
+                    expected: Expected::Header,
+                    end: 44
+                }
+            }),
+            r
+        );
+        assert_eq!(r, super::super::stream::decode("0000580000B102".as_bytes()));
+    }
+
+    #[test]
+    fn decode_lenient_test() {
+        let r = packet("38006F4529").decode_lenient();
+        assert_eq!(Some("(lt 10)".to_string()), r.packet.map(|p| p.to_string()));
+        assert_eq!(1, r.diagnostics.len());
+
+        let r = packet("38006F45291200").decode_lenient();
+        assert_eq!(Some(1), r.packet.map(|p| p.run().unwrap()));
+        assert!(r.diagnostics.is_empty());
+
+        // Padding with a 1 in it
+        let r = packet("D2FE29").decode_lenient();
+        assert_eq!(Some(2021), r.packet.and_then(|p| p.run().ok()));
+        assert_eq!(
+            vec![DecodeError {
+                offset: 23,
+                path: vec![],
+                kind: DecodeErrorKind::NonZeroPadding
+            }],
+            r.diagnostics
+        );
+        assert!(packet("D2FE29").decode_checked().is_ok());
+
+        let r = packet("D").decode_lenient();
+        assert_eq!(None, r.packet);
+    }
+}
@@ -0,0 +1,281 @@
+//! Evaluating packets without overflowing: either failing with an [EvalError] that says which
+//! packet overflowed, or (with the `bigint` feature) working with numbers of any size.
+
+use std::fmt::{Display, Formatter};
+use std::result::Result as StdResult;
+
+#[cfg(feature = "bigint")]
+use num_bigint::BigUint;
+
+use super::operator_name;
+use super::DecodedPacket::{self, *};
+
+/// Numbers that packets can be evaluated to
+pub trait Value: Ord + Clone {
+    fn from_usize(n: usize) -> Self;
+    fn checked_add(&self, other: &Self) -> Option<Self>;
+    fn checked_mul(&self, other: &Self) -> Option<Self>;
+}
+
+impl Value for usize {
+    fn from_usize(n: usize) -> Self {
+        n
+    }
+
+    fn checked_add(&self, other: &Self) -> Option<Self> {
+        usize::checked_add(*self, *other)
+    }
+
+    fn checked_mul(&self, other: &Self) -> Option<Self> {
+        usize::checked_mul(*self, *other)
+    }
+}
+
+#[cfg(feature = "bigint")]
+impl Value for BigUint {
+    fn from_usize(n: usize) -> Self {
+        BigUint::from(n)
+    }
+
+    fn checked_add(&self, other: &Self) -> Option<Self> {
+        Some(self + other)
+    }
+
+    fn checked_mul(&self, other: &Self) -> Option<Self> {
+        Some(self * other)
+    }
+}
+
+/// Why a packet could not be evaluated, and where it is
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct EvalError {
+    /// Index of the sub-packet taken at each level to get from the outermost packet to the one
+    /// that failed; empty if it was the outermost packet
+    pub path: Vec<usize>,
+    pub kind: EvalErrorKind,
+}
+
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum EvalErrorKind {
+    Overflow { packet_type: usize },
+    NoSubPackets { packet_type: usize },
+    NotTwoSubPackets { packet_type: usize, count: usize },
+    UnsupportedPacketType(usize),
+    End,
+}
+
+impl Display for EvalError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} at packet path {:?}", self.kind, self.path)
+    }
+}
+
+impl std::error::Error for EvalError {}
+
+impl Display for EvalErrorKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EvalErrorKind::Overflow { packet_type } => {
+                write!(f, "Overflow in {}", operator_name(*packet_type))
+            }
+            EvalErrorKind::NoSubPackets { packet_type } => {
+                write!(f, "No sub-packets for {}", operator_name(*packet_type))
+            }
+            EvalErrorKind::NotTwoSubPackets { packet_type, count } => write!(
+                f,
+                "Did not have 2 sub-packets for {}, but [{}]",
+                operator_name(*packet_type),
+                count
+            ),
+            EvalErrorKind::UnsupportedPacketType(packet_type) => {
+                write!(f, "Unsupported packet type [{}]", packet_type)
+            }
+            EvalErrorKind::End => write!(f, "No result for end"),
+        }
+    }
+}
+
+impl std::error::Error for EvalErrorKind {}
+
+impl DecodedPacket {
+    /// Like [DecodedPacket::run], but saying which packet went wrong, e.g. which product
+    /// overflowed
+    pub fn run_checked(&self) -> StdResult<usize, EvalError> {
+        self.evaluate()
+    }
+
+    /// Like [DecodedPacket::run], but never overflowing
+    #[cfg(feature = "bigint")]
+    pub fn run_big(&self) -> StdResult<BigUint, EvalError> {
+        self.evaluate()
+    }
+
+    pub fn evaluate<V: Value>(&self) -> StdResult<V, EvalError> {
+        fn evaluate_at<V: Value>(
+            packet: &DecodedPacket,
+            path: &mut Vec<usize>,
+        ) -> StdResult<V, EvalError> {
+            let fail = |path: &[usize], kind| EvalError {
+                path: path.to_vec(),
+                kind,
+            };
+            match packet {
+                Literal { number, .. } => Ok(V::from_usize(*number)),
+                End => Err(fail(path, EvalErrorKind::End)),
+                operator => {
+                    let (packet_type, sub_packets) = operator
+                        .operator_parts()
+                        .map_err(|_| fail(path, EvalErrorKind::End))?;
+                    let mut results = Vec::with_capacity(sub_packets.len());
+                    for (idx, sub_packet) in sub_packets.iter().enumerate() {
+                        path.push(idx);
+                        results.push(evaluate_at(sub_packet, path)?);
+                        path.pop();
+                    }
+                    apply(packet_type, &results).map_err(|kind| fail(path, kind))
+                }
+            }
+        }
+
+        evaluate_at(self, &mut vec![])
+    }
+}
+
+pub(super) fn apply<V: Value>(packet_type: usize, results: &[V]) -> StdResult<V, EvalErrorKind> {
+    let overflow = EvalErrorKind::Overflow { packet_type };
+    let no_sub_packets = EvalErrorKind::NoSubPackets { packet_type };
+    match packet_type {
+        0 => results
+            .iter()
+            .try_fold(V::from_usize(0), |acc, next| acc.checked_add(next))
+            .ok_or(overflow),
+        1 => results
+            .iter()
+            .try_fold(V::from_usize(1), |acc, next| acc.checked_mul(next))
+            .ok_or(overflow),
+        2 => results.iter().min().cloned().ok_or(no_sub_packets),
+        3 => results.iter().max().cloned().ok_or(no_sub_packets),
+        5..=7 => match results {
+            [first, second] => {
+                let r = match packet_type {
+                    5 => first > second,
+                    6 => first < second,
+                    _ => first == second,
+                };
+                Ok(V::from_usize(r as usize))
+            }
+            _ => Err(EvalErrorKind::NotTwoSubPackets {
+                packet_type,
+                count: results.len(),
+            }),
+        },
+        other => Err(EvalErrorKind::UnsupportedPacketType(other)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 2^32 * 2^32 doesn't fit, but each factor does
+    fn overflowing_product() -> DecodedPacket {
+        DecodedPacket::operator_type_1(
+            0,
+            1,
+            vec![
+                DecodedPacket::literal(0, 1 << 32),
+                DecodedPacket::literal(0, 1 << 32),
+            ],
+        )
+    }
+
+    #[test]
+    fn run_checked_test() {
+        let i = super::super::parse(super::super::INPUT).unwrap();
+        let decoded = i.packet.decode().unwrap();
+        assert_eq!(Ok(1148595959144), decoded.run_checked());
+
+        let r = overflowing_product().run_checked();
+        assert_eq!(
+            Err(EvalError {
+                path: vec![],
+                kind: EvalErrorKind::Overflow { packet_type: 1 }
+            }),
+            r
+        );
+    }
+
+    #[test]
+    fn run_checked_path_test() {
+        let nested = DecodedPacket::operator_type_0(
+            0,
+            3,
+            vec![
+                DecodedPacket::literal(0, 1),
+                DecodedPacket::operator_type_0(
+                    0,
+                    0,
+                    vec![DecodedPacket::literal(0, 2), overflowing_product()],
+                ),
+            ],
+        );
+        let err = nested.run_checked().unwrap_err();
+        assert_eq!(vec![1, 1], err.path);
+        assert_eq!("Overflow in product at packet path [1, 1]", err.to_string());
+        assert!(nested.run().is_err());
+
+        let too_few = DecodedPacket::operator_type_0(
+            0,
+            0,
+            vec![DecodedPacket::operator_type_0(
+                0,
+                7,
+                vec![DecodedPacket::literal(0, 1)],
+            )],
+        );
+        assert_eq!(
+            Err(EvalError {
+                path: vec![0],
+                kind: EvalErrorKind::NotTwoSubPackets {
+                    packet_type: 7,
+                    count: 1
+                }
+            }),
+            too_few.run_checked()
+        );
+
+        let too_many = DecodedPacket::operator_type_1(
+            0,
+            5,
+            vec![
+                DecodedPacket::literal(0, 3),
+                DecodedPacket::literal(0, 2),
+                DecodedPacket::literal(0, 1),
+            ],
+        );
+        assert_eq!(
+            Err(EvalError {
+                path: vec![],
+                kind: EvalErrorKind::NotTwoSubPackets {
+                    packet_type: 5,
+                    count: 3
+                }
+            }),
+            too_many.run_checked()
+        );
+    }
+
+    #[cfg(feature = "bigint")]
+    #[test]
+    fn run_big_test() {
+        let r = overflowing_product().run_big().unwrap();
+        assert_eq!("18446744073709551616", r.to_string());
+
+        let i = super::super::parse(super::super::INPUT).unwrap();
+        let decoded = i.packet.decode().unwrap();
+        assert_eq!(
+            BigUint::from(1148595959144usize),
+            decoded.run_big().unwrap()
+        );
+    }
+}
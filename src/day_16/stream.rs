@@ -0,0 +1,542 @@
+//! Decoding transmissions a few bits at a time, straight from a reader of hex, so that memory use
+//! depends on how deeply packets are nested rather than how long the transmission is.
+
+use std::fmt::{Display, Formatter};
+use std::io::{BufReader, Bytes, Read};
+use std::result::Result as StdResult;
+
+use super::decode::{DecodeError, DecodeErrorKind, Expected};
+use super::DecodedPacket::{self, *};
+use super::LITERAL_PACKET_TYPE;
+
+/// What is found in a transmission, in order.
+///
+/// Every [Event::OperatorStart] is eventually matched by an [Event::OperatorEnd], after the
+/// events of its sub-packets.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub enum Event {
+    Literal {
+        packet_version: usize,
+        packet_type: usize,
+        number: BigLiteral,
+        bits_used: usize,
+    },
+    OperatorStart {
+        packet_version: usize,
+        packet_type: usize,
+        length: SubPacketLength,
+    },
+    OperatorEnd {
+        packet_type: usize,
+        bits_used: usize,
+    },
+}
+
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum SubPacketLength {
+    /// Length type 0
+    Bits(usize),
+    /// Length type 1
+    Count(usize),
+}
+
+/// Decodes the transmission in `reader`, which holds hex (whitespace is skipped), one [Event] at
+/// a time. Reading stops once the outermost packet is done, so any padding is left alone.
+pub fn events<R: Read>(reader: R) -> Events<R> {
+    Events {
+        bits: HexBits {
+            bytes: BufReader::new(reader).bytes(),
+            nibble: 0,
+            bits_left_in_nibble: 0,
+            offset: 0,
+        },
+        open_operators: vec![],
+        started: false,
+        finished: false,
+    }
+}
+
+/// Builds the whole [DecodedPacket] tree from `reader`, without holding all of its bits.
+///
+/// Fails with [DecodeErrorKind::LiteralTooWide] if a literal doesn't fit in a `usize`; use
+/// [events] to get at those.
+pub fn decode<R: Read>(reader: R) -> StdResult<DecodedPacket, DecodeError> {
+    struct Partial {
+        packet_version: usize,
+        packet_type: usize,
+        length: SubPacketLength,
+        sub_packets: Vec<DecodedPacket>,
+    }
+
+    let mut partials: Vec<Partial> = vec![];
+    let mut events = events(reader);
+    // Not a for loop, as failures need the offset events have got to
+    while let Some(event) = events.next() {
+        let done = match event? {
+            Event::Literal {
+                packet_version,
+                packet_type,
+                number,
+                bits_used,
+            } => Literal {
+                packet_version,
+                packet_type,
+                number: number.to_usize().ok_or_else(|| DecodeError {
+                    offset: events.bits.offset - bits_used,
+                    path: partials.iter().map(|p| p.sub_packets.len()).collect(),
+                    kind: DecodeErrorKind::LiteralTooWide,
+                })?,
+                bits_from_packet_used: bits_used,
+            },
+            Event::OperatorStart {
+                packet_version,
+                packet_type,
+                length,
+            } => {
+                partials.push(Partial {
+                    packet_version,
+                    packet_type,
+                    length,
+                    sub_packets: vec![],
+                });
+                continue;
+            }
+            Event::OperatorEnd { bits_used, .. } => {
+                let partial = partials.pop().expect("operators end after they start");
+                match partial.length {
+                    SubPacketLength::Bits(sub_packet_bit_length) => OperatorType0 {
+                        packet_version: partial.packet_version,
+                        packet_type: partial.packet_type,
+                        sub_packet_bit_length,
+                        bits_from_packet_used: bits_used,
+                        sub_packets: partial.sub_packets,
+                    },
+                    SubPacketLength::Count(sub_packet_count) => OperatorType1 {
+                        packet_version: partial.packet_version,
+                        packet_type: partial.packet_type,
+                        sub_packet_count,
+                        bits_from_packet_used: bits_used,
+                        sub_packets: partial.sub_packets,
+                    },
+                }
+            }
+        };
+        match partials.last_mut() {
+            Some(parent) => parent.sub_packets.push(done),
+            None => return Ok(done),
+        }
+    }
+    // Events only stop early on a failure, but say where they stopped anyway
+    Err(DecodeError {
+        offset: events.bits.offset,
+        path: vec![],
+        kind: DecodeErrorKind::Truncated {
+            expected: Expected::Header,
+        },
+    })
+}
+
+pub struct Events<R> {
+    bits: HexBits<R>,
+    open_operators: Vec<OpenOperator>,
+    started: bool,
+    finished: bool,
+}
+
+struct OpenOperator {
+    packet_type: usize,
+    start_offset: usize,
+    remaining: Remaining,
+    sub_packets_done: usize,
+}
+
+enum Remaining {
+    UntilOffset(usize),
+    Packets(usize),
+}
+
+impl<R: Read> Iterator for Events<R> {
+    type Item = StdResult<Event, DecodeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+        let r = self.next_event();
+        match &r {
+            Ok(None) | Err(_) => self.finished = true,
+            Ok(Some(_)) => (),
+        }
+        r.transpose()
+    }
+}
+
+impl<R: Read> Events<R> {
+    fn next_event(&mut self) -> StdResult<Option<Event>, DecodeError> {
+        if let Some(open) = self.open_operators.last() {
+            let offset = self.bits.offset;
+            let is_done = match open.remaining {
+                Remaining::UntilOffset(end) => offset == end,
+                Remaining::Packets(count) => count == 0,
+            };
+            if is_done {
+                let packet_type = open.packet_type;
+                let bits_used = offset - open.start_offset;
+                self.open_operators.pop();
+                self.packet_done();
+                return Ok(Some(Event::OperatorEnd {
+                    packet_type,
+                    bits_used,
+                }));
+            }
+        } else if self.started {
+            return Ok(None);
+        }
+        self.started = true;
+
+        let start_offset = self.bits.offset;
+        let header = self.read(6, Expected::Header)?;
+        let (packet_version, packet_type) = (header >> 3, header & 0b111);
+        if packet_type == LITERAL_PACKET_TYPE {
+            let mut nibbles = vec![];
+            loop {
+                let group = self.read(5, Expected::LiteralGroup)?;
+                nibbles.push((group & 0b1111) as u8);
+                let is_last_group = group >> 4 == 0;
+                if is_last_group {
+                    break;
+                }
+            }
+            self.packet_done();
+            Ok(Some(Event::Literal {
+                packet_version,
+                packet_type,
+                number: BigLiteral::from_nibbles(nibbles),
+                bits_used: self.bits.offset - start_offset,
+            }))
+        } else {
+            let (length, remaining) = if self.read(1, Expected::LengthType)? == 0 {
+                let bit_length = self.read(15, Expected::SubPacketBitLength)?;
+                (
+                    SubPacketLength::Bits(bit_length),
+                    Remaining::UntilOffset(self.bits.offset + bit_length),
+                )
+            } else {
+                let count = self.read(11, Expected::SubPacketCount)?;
+                (SubPacketLength::Count(count), Remaining::Packets(count))
+            };
+            self.open_operators.push(OpenOperator {
+                packet_type,
+                start_offset,
+                remaining,
+                sub_packets_done: 0,
+            });
+            Ok(Some(Event::OperatorStart {
+                packet_version,
+                packet_type,
+                length,
+            }))
+        }
+    }
+
+    // Reads can't go past where the sub-packets of any open type 0 operator end, as an
+    // operator's length may claim more than its parent allows
+    fn read(&mut self, width: usize, expected: Expected) -> StdResult<usize, DecodeError> {
+        let offset = self.bits.offset;
+        let sub_packets_end = self
+            .open_operators
+            .iter()
+            .filter_map(|open| match open.remaining {
+                Remaining::UntilOffset(end) => Some(end),
+                Remaining::Packets(_) => None,
+            })
+            .min();
+        let r = match sub_packets_end {
+            Some(end) if offset + width > end => {
+                Err(DecodeErrorKind::PastSubPackets { expected, end })
+            }
+            _ => self.bits.read(width, expected),
+        };
+        r.map_err(|kind| DecodeError {
+            // Where the bad hex is, rather than where the field it's in started
+            offset: match kind {
+                DecodeErrorKind::InvalidHex(_) | DecodeErrorKind::Unreadable(_) => self.bits.offset,
+                _ => offset,
+            },
+            path: self
+                .open_operators
+                .iter()
+                .map(|open| open.sub_packets_done)
+                .collect(),
+            kind,
+        })
+    }
+
+    // Counts a finished packet towards its operator, if it has one
+    fn packet_done(&mut self) {
+        if let Some(open) = self.open_operators.last_mut() {
+            open.sub_packets_done += 1;
+            if let Remaining::Packets(count) = &mut open.remaining {
+                *count = count.saturating_sub(1);
+            }
+        }
+    }
+}
+
+struct HexBits<R> {
+    bytes: Bytes<BufReader<R>>,
+    nibble: u8,
+    bits_left_in_nibble: usize,
+    // How many bits have been read so far
+    offset: usize,
+}
+
+impl<R: Read> HexBits<R> {
+    // Up to a usize worth of bits, big-endian
+    fn read(&mut self, width: usize, expected: Expected) -> StdResult<usize, DecodeErrorKind> {
+        let mut acc = 0;
+        for _ in 0..width {
+            if self.bits_left_in_nibble == 0 {
+                self.nibble = self.next_nibble(expected)?;
+                self.bits_left_in_nibble = 4;
+            }
+            self.bits_left_in_nibble -= 1;
+            acc = (acc << 1) | ((self.nibble >> self.bits_left_in_nibble) & 1) as usize;
+            self.offset += 1;
+        }
+        Ok(acc)
+    }
+
+    fn next_nibble(&mut self, expected: Expected) -> StdResult<u8, DecodeErrorKind> {
+        loop {
+            let byte = match self.bytes.next() {
+                Some(Ok(byte)) => byte,
+                Some(Err(e)) => return Err(DecodeErrorKind::Unreadable(e.kind())),
+                None => return Err(DecodeErrorKind::Truncated { expected }),
+            };
+            let c = byte as char;
+            if !c.is_ascii_whitespace() {
+                return c
+                    .to_digit(16)
+                    .map(|d| d as u8)
+                    .ok_or(DecodeErrorKind::InvalidHex(c));
+            }
+        }
+    }
+}
+
+/// A literal's number, however many bits it takes
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct BigLiteral {
+    // Most significant first, without leading zeros
+    nibbles: Vec<u8>,
+}
+
+impl BigLiteral {
+    pub fn from_nibbles(nibbles: Vec<u8>) -> BigLiteral {
+        let leading_zeros = nibbles.iter().take_while(|n| **n == 0).count();
+        BigLiteral {
+            nibbles: nibbles[leading_zeros..].to_vec(),
+        }
+    }
+
+    /// Most significant first
+    pub fn nibbles(&self) -> &[u8] {
+        &self.nibbles
+    }
+
+    pub fn to_usize(&self) -> Option<usize> {
+        if self.nibbles.len() * 4 > usize::BITS as usize {
+            None
+        } else {
+            Some(
+                self.nibbles
+                    .iter()
+                    .fold(0, |acc, nibble| (acc << 4) | *nibble as usize),
+            )
+        }
+    }
+}
+
+impl From<usize> for BigLiteral {
+    fn from(n: usize) -> BigLiteral {
+        let nibbles = (0..usize::BITS / 4)
+            .rev()
+            .map(|idx| ((n >> (idx * 4)) & 0b1111) as u8)
+            .collect();
+        BigLiteral::from_nibbles(nibbles)
+    }
+}
+
+/// In decimal
+impl Display for BigLiteral {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        // Long division by 10, over and over
+        let mut digits = vec![];
+        let mut remaining = self.nibbles.clone();
+        while !remaining.is_empty() {
+            let mut remainder = 0;
+            let mut quotient = Vec::with_capacity(remaining.len());
+            for nibble in remaining {
+                let current = remainder * 16 + nibble;
+                if !(quotient.is_empty() && current / 10 == 0) {
+                    quotient.push(current / 10);
+                }
+                remainder = current % 10;
+            }
+            digits.push(remainder);
+            remaining = quotient;
+        }
+        if digits.is_empty() {
+            write!(f, "0")
+        } else {
+            let s: String = digits.iter().rev().map(|d| char::from(b'0' + d)).collect();
+            write!(f, "{}", s)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::parse;
+    use super::*;
+
+    // Hands out one byte per read, like a slow pipe
+    struct Trickle<'a>(&'a [u8]);
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            match (self.0.split_first(), buf.first_mut()) {
+                (Some((first, rest)), Some(b)) => {
+                    *b = *first;
+                    self.0 = rest;
+                    Ok(1)
+                }
+                _ => Ok(0),
+            }
+        }
+    }
+
+    #[test]
+    fn events_test() {
+        let r: Vec<Event> = events("38006F45291200".as_bytes())
+            .collect::<StdResult<_, _>>()
+            .unwrap();
+        assert_eq!(
+            vec![
+                Event::OperatorStart {
+                    packet_version: 1,
+                    packet_type: 6,
+                    length: SubPacketLength::Bits(27)
+                },
+                Event::Literal {
+                    packet_version: 6,
+                    packet_type: 4,
+                    number: BigLiteral::from(10),
+                    bits_used: 11
+                },
+                Event::Literal {
+                    packet_version: 2,
+                    packet_type: 4,
+                    number: BigLiteral::from(20),
+                    bits_used: 16
+                },
+                Event::OperatorEnd {
+                    packet_type: 6,
+                    bits_used: 49
+                },
+            ],
+            r
+        );
+    }
+
+    #[test]
+    fn decode_matches_packet_decode_test() {
+        for hex in [
+            "D2FE28",
+            "38006F45291200",
+            "EE00D40C823060",
+            "8A004A801A8002F478",
+            "620080001611562C8802118E34",
+            "C0015000016115A2E0802F182340",
+            "A0016C880162017C3686B18A3D4780",
+            "9C0141080250320F1802104A08",
+            super::super::INPUT,
+        ] {
+            let expected = parse(hex.trim()).unwrap().packet.decode().unwrap();
+            assert_eq!(expected, decode(Trickle(hex.as_bytes())).unwrap());
+        }
+    }
+
+    #[test]
+    fn decode_errors_test() {
+        // Cut off part way through, failing just as Packet::decode_checked does
+        let expected = parse("38006F4529").unwrap().packet.decode_checked();
+        assert_eq!(expected, decode("38006F4529".as_bytes()));
+        assert_eq!(
+            Err(DecodeError {
+                offset: 0,
+                path: vec![],
+                kind: DecodeErrorKind::Truncated {
+                    expected: Expected::Header
+                }
+            }),
+            decode("".as_bytes())
+        );
+
+        assert_eq!(
+            Err(DecodeError {
+                offset: 20,
+                path: vec![],
+                kind: DecodeErrorKind::InvalidHex('G')
+            }),
+            decode("38006G45291200".as_bytes())
+        );
+    }
+
+    #[test]
+    fn wide_literal_test() {
+        // 2^64 + 1, which takes 17 groups
+        let packet = {
+            let mut bits: Vec<u8> = vec![0, 0, 0, 1, 0, 0];
+            for group_idx in 0..17 {
+                bits.push(if group_idx == 16 { 0 } else { 1 });
+                let nibble = if group_idx == 0 || group_idx == 16 {
+                    1
+                } else {
+                    0
+                };
+                bits.extend([0, 0, 0, nibble]);
+            }
+            super::super::Packet(bits)
+        };
+        let hex = packet.to_hex();
+        let r: Vec<Event> = events(hex.as_bytes()).collect::<StdResult<_, _>>().unwrap();
+        match &r[..] {
+            [Event::Literal { number, .. }] => {
+                assert_eq!("18446744073709551617", number.to_string());
+                assert_eq!(None, number.to_usize());
+            }
+            other => panic!("Unexpected events {:?}", other),
+        }
+        assert_eq!(
+            Err(DecodeErrorKind::LiteralTooWide),
+            decode(hex.as_bytes()).map_err(|e| e.kind)
+        );
+        assert!(packet.decode().is_err());
+    }
+
+    #[test]
+    fn big_literal_test() {
+        assert_eq!("0", BigLiteral::from(0).to_string());
+        assert_eq!("2021", BigLiteral::from(2021).to_string());
+        assert_eq!(
+            usize::MAX.to_string(),
+            BigLiteral::from(usize::MAX).to_string()
+        );
+        assert_eq!(
+            Some(2021),
+            BigLiteral::from_nibbles(vec![0, 0, 7, 14, 5]).to_usize()
+        );
+    }
+}
@@ -1,23 +1,40 @@
 use std::result::Result as StdResult;
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use combine::parser::char::*;
 use combine::*;
 
-use crate::common::isize_parser;
+use crate::common::{isize_parser, parse_all};
+use crate::error;
+use crate::solution::Solution;
 
 pub const INPUT: &str = include_str!("../data/day_17_input");
 
-pub fn run() -> Result<()> {
-    println!("*** Day 17: Trick Shot ***");
-    println!("Input: {}", INPUT);
-    let input = parse(INPUT)?;
-    let sol_1 = input.highest_height_that_hits_target();
-    println!("Solution 1: {:?}", sol_1);
-    let sol_2 = input.distinct_velocities_that_hit_target();
-    println!("Solution 2: {:?}", sol_2);
+pub struct Day17;
 
-    Ok(())
+impl Solution for Day17 {
+    const DAY: usize = 17;
+    const TITLE: &'static str = "Trick Shot";
+    const INPUT: &'static str = INPUT;
+
+    type Input = Input;
+    type Part1 = isize;
+    type Part2 = usize;
+
+    fn parse(s: &str) -> Result<Self::Input> {
+        Ok(parse(s).map_err(|e| e.on_day(Self::DAY))?)
+    }
+
+    fn part_1(input: &Self::Input) -> Result<Self::Part1> {
+        input
+            .highest_height_that_hits_target()
+            .map(|y| y.0)
+            .context("No velocity hits the target")
+    }
+
+    fn part_2(input: &Self::Input) -> Result<Self::Part2> {
+        Ok(input.distinct_velocities_that_hit_target())
+    }
 }
 
 #[derive(Debug, Eq, PartialEq)]
@@ -132,8 +149,8 @@ impl Input {
     }
 }
 
-pub fn parse(s: &str) -> StdResult<Input, easy::ParseError<&str>> {
-    let mut parser = string("target area: ")
+pub fn parse(s: &str) -> StdResult<Input, error::ParseError> {
+    let parser = string("target area: ")
         .with(
             string("x=")
                 .with(isize_parser())
@@ -153,8 +170,7 @@ pub fn parse(s: &str) -> StdResult<Input, easy::ParseError<&str>> {
             target_y_min: Y(y1.min(y2)),
             target_y_max: Y(y1.max(y2)),
         });
-    let (r, _) = parser.easy_parse(s)?;
-    Ok(r)
+    parse_all(parser, s)
 }
 
 #[cfg(test)]
@@ -1,8 +1,12 @@
+use std::cmp::Ordering;
+use std::fmt::{Display, Formatter};
+use std::iter::Sum;
 use std::num::ParseIntError;
 use std::ops::Add;
 use std::result::Result as StdResult;
+use std::str::FromStr;
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use combine::parser::char::*;
 use combine::*;
 use itertools::Either;
@@ -10,26 +14,54 @@ use itertools::Either::*;
 
 use PairTree::*;
 
-use crate::common::usize_parser;
+use crate::common::{parse_all, usize_parser, PositionedStream};
+use crate::error;
+use crate::solution::Solution;
+pub use flat::FlatNumber;
+
+mod flat;
 
 pub const INPUT: &str = include_str!("../data/day_18_input");
 
-pub fn run() -> Result<()> {
-    println!("*** Day 18: Snailfish ***");
-    println!("Input: {}", INPUT);
-    let input = parse(INPUT)?;
-    let sol_1 = add_all_magnitude(&input);
-    println!("Solution 1: {:?}", sol_1);
-    let sol_2 = biggest_pair_sum(&input);
-    println!("Solution 2: {:?}", sol_2);
+pub struct Day18;
+
+impl Solution for Day18 {
+    const DAY: usize = 18;
+    const TITLE: &'static str = "Snailfish";
+    const INPUT: &'static str = INPUT;
+
+    type Input = Input;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(s: &str) -> Result<Self::Input> {
+        Ok(parse(s).map_err(|e| e.on_day(Self::DAY))?)
+    }
+
+    fn part_1(input: &Self::Input) -> Result<Self::Part1> {
+        add_all_magnitude_as::<FlatNumber>(input).context("No numbers to add")
+    }
 
-    Ok(())
+    fn part_2(input: &Self::Input) -> Result<Self::Part2> {
+        // Flat numbers add up several times faster than trees
+        let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+        biggest_pair_sum_parallel::<FlatNumber>(input, threads)
+            .map(|biggest| biggest.magnitude)
+            .context("Fewer than 2 numbers to add")
+    }
 }
 
 #[derive(Debug, Eq, PartialEq)]
-pub struct Input(Vec<PairTree>);
+pub struct Input(pub Vec<SnailfishNumber>);
 
+/// A snailfish number, which reads and prints as e.g. `[[1,2],3]`, and is always reduced after
+/// being added to.
+///
+/// Ordered by magnitude, with numbers of the same magnitude ordered by their [PairTree]s.
 #[derive(Debug, Eq, PartialEq, Clone)]
+pub struct SnailfishNumber(PairTree);
+
+#[derive(Debug, Eq, PartialEq, Ord, PartialOrd, Clone)]
 pub enum PairTree {
     Num(usize),
     Pair(Box<PairTree>, Box<PairTree>),
@@ -52,19 +84,23 @@ impl PairTree {
     /// 1. Explode
     /// 2. Split
     ///
-    /// If there are any changes at any step (returns false), repeat, otherwise exit.
+    /// If there are any changes at any step, repeat, otherwise exit.
     fn reduce(&mut self) {
-        loop {
-            if self.explode() && self.split() {
-                break;
-            }
-        }
+        while self.reduce_once().is_some() {}
+    }
+
+    /// One explosion or, if there is nothing to explode, one split
+    fn reduce_once(&mut self) -> Option<Action> {
+        self.explode().or_else(|| self.split())
     }
 
-    /// Returns true if no changes, false otherwise
-    fn explode(&mut self) -> bool {
-        fn explode_subtree(depth: usize, p: &mut PairTree) -> Either<SubTreeExplosionResult, ()> {
-            match (depth, &p) {
+    /// Explodes the leftmost pair nested inside 4 others, if there is one
+    fn explode(&mut self) -> Option<Action> {
+        fn explode_subtree(
+            path: &mut Vec<Side>,
+            p: &mut PairTree,
+        ) -> Either<SubTreeExplosionResult, ()> {
+            match (path.len(), &p) {
                 (_, Num(_)) => Right(()), // Do nothing
                 (depth, Pair(left, right)) => {
                     match (left.as_ref(), right.as_ref()) {
@@ -75,6 +111,10 @@ impl PairTree {
                             Left(SubTreeExplosionResult {
                                 pre_explosion_left: left_value,
                                 pre_explosion_right: right_value,
+                                action: Action::Explode {
+                                    path: path.clone(),
+                                    pair: (left_value, right_value),
+                                },
                             })
                         }
                         _ => {
@@ -82,30 +122,42 @@ impl PairTree {
                             match p {
                                 Pair(left_tree, right_tree) => {
                                     // Explode left first according to instructions
-                                    match explode_subtree(depth + 1, left_tree) {
+                                    path.push(Side::Left);
+                                    let left_explosion = explode_subtree(path, left_tree);
+                                    path.pop();
+                                    match left_explosion {
                                         Left(SubTreeExplosionResult {
                                             pre_explosion_left,
                                             pre_explosion_right,
+                                            action,
                                         }) => {
                                             add_to_leftmost(right_tree, pre_explosion_right);
                                             Left(SubTreeExplosionResult {
                                                 pre_explosion_left,
                                                 pre_explosion_right: 0,
+                                                action,
                                             })
                                         }
-                                        Right(()) => match explode_subtree(depth + 1, right_tree) {
-                                            Left(SubTreeExplosionResult {
-                                                pre_explosion_left,
-                                                pre_explosion_right,
-                                            }) => {
-                                                add_to_rightmost(left_tree, pre_explosion_left);
+                                        Right(()) => {
+                                            path.push(Side::Right);
+                                            let right_explosion = explode_subtree(path, right_tree);
+                                            path.pop();
+                                            match right_explosion {
                                                 Left(SubTreeExplosionResult {
-                                                    pre_explosion_left: 0,
+                                                    pre_explosion_left,
                                                     pre_explosion_right,
-                                                })
+                                                    action,
+                                                }) => {
+                                                    add_to_rightmost(left_tree, pre_explosion_left);
+                                                    Left(SubTreeExplosionResult {
+                                                        pre_explosion_left: 0,
+                                                        pre_explosion_right,
+                                                        action,
+                                                    })
+                                                }
+                                                Right(()) => Right(()),
                                             }
-                                            Right(()) => Right(()),
-                                        },
+                                        }
                                     }
                                 }
                                 _ => unreachable!(), // already matched on Num
@@ -119,6 +171,7 @@ impl PairTree {
         struct SubTreeExplosionResult {
             pre_explosion_left: usize,
             pre_explosion_right: usize,
+            action: Action,
         }
 
         fn add_to_leftmost(pair: &mut PairTree, num: usize) {
@@ -139,27 +192,111 @@ impl PairTree {
             }
         }
 
-        explode_subtree(0, self).is_right()
+        explode_subtree(&mut vec![], self).left().map(|r| r.action)
     }
 
-    fn split(&mut self) -> bool {
-        match self {
-            Num(ref n) => {
-                if *n >= 10 {
-                    *self = PairTree::pair(
-                        Num((*n as f64 / 2f64).floor() as usize),
-                        Num((*n as f64 / 2f64).ceil() as usize),
-                    );
-                    false
-                } else {
-                    true
+    /// Splits the leftmost number that is 10 or more, if there is one
+    fn split(&mut self) -> Option<Action> {
+        fn split_subtree(path: &mut Vec<Side>, p: &mut PairTree) -> Option<Action> {
+            match p {
+                Num(ref n) => {
+                    if *n >= 10 {
+                        let number = *n;
+                        *p = PairTree::pair(Num(number / 2), Num(number.div_ceil(2)));
+                        Some(Action::Split {
+                            path: path.clone(),
+                            number,
+                        })
+                    } else {
+                        None
+                    }
+                }
+                Pair(left, right) => {
+                    path.push(Side::Left);
+                    let split = split_subtree(path, left);
+                    path.pop();
+                    split.or_else(|| {
+                        path.push(Side::Right);
+                        let split = split_subtree(path, right);
+                        path.pop();
+                        split
+                    })
                 }
             }
-            Pair(left, right) => left.split() && right.split(),
+        }
+
+        split_subtree(&mut vec![], self)
+    }
+}
+
+/// Which way to go from a pair, to get to a number or pair inside it
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum Side {
+    Left,
+    Right,
+}
+
+/// Something that happens while adding snailfish numbers. `path`s lead from the outermost pair.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub enum Action {
+    /// The two numbers were paired up, before reducing
+    Add,
+    Explode {
+        path: Vec<Side>,
+        pair: (usize, usize),
+    },
+    Split {
+        path: Vec<Side>,
+        number: usize,
+    },
+}
+
+/// The number after an [Action]
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct Step {
+    pub action: Action,
+    pub number: SnailfishNumber,
+}
+
+/// From [SnailfishNumber::add_steps]
+pub struct ReductionSteps {
+    current: PairTree,
+    added: bool,
+}
+
+impl Iterator for ReductionSteps {
+    type Item = Step;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let action = if self.added {
+            self.current.reduce_once()?
+        } else {
+            self.added = true;
+            Action::Add
+        };
+        Some(Step {
+            action,
+            number: SnailfishNumber(self.current.clone()),
+        })
+    }
+}
+
+impl Display for Action {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Action::Add => write!(f, "addition"),
+            Action::Explode { pair, .. } => write!(f, "explode [{},{}]", pair.0, pair.1),
+            Action::Split { number, .. } => write!(f, "split {}", number),
         }
     }
 }
 
+impl Display for Step {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "after {}: {}", self.action, self.number)
+    }
+}
+
 impl Add for PairTree {
     type Output = PairTree;
 
@@ -170,19 +307,131 @@ impl Add for PairTree {
     }
 }
 
-fn add_all_magnitude(input: &Input) -> Option<usize> {
-    let final_tree = input.0.clone().into_iter().reduce(|acc, next| acc + next);
-    final_tree.map(|p| p.magnitude())
+impl Display for PairTree {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Num(n) => write!(f, "{}", n),
+            Pair(left, right) => write!(f, "[{},{}]", left, right),
+        }
+    }
+}
+
+impl SnailfishNumber {
+    pub fn magnitude(&self) -> usize {
+        self.0.magnitude()
+    }
+
+    pub fn tree(&self) -> &PairTree {
+        &self.0
+    }
+
+    /// Adds `rhs`, one [Step] at a time. The last step has the sum.
+    pub fn add_steps(self, rhs: SnailfishNumber) -> ReductionSteps {
+        ReductionSteps {
+            current: PairTree::pair(self.0, rhs.0),
+            added: false,
+        }
+    }
+}
+
+impl Add for SnailfishNumber {
+    type Output = SnailfishNumber;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        SnailfishNumber(self.0 + rhs.0)
+    }
+}
+
+impl Add for &SnailfishNumber {
+    type Output = SnailfishNumber;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        self.clone() + rhs.clone()
+    }
+}
+
+/// There's no snailfish zero, so the sum of no numbers is None
+impl Sum<SnailfishNumber> for Option<SnailfishNumber> {
+    fn sum<I: Iterator<Item = SnailfishNumber>>(iter: I) -> Self {
+        iter.reduce(|acc, next| acc + next)
+    }
+}
+
+impl<'a> Sum<&'a SnailfishNumber> for Option<SnailfishNumber> {
+    fn sum<I: Iterator<Item = &'a SnailfishNumber>>(iter: I) -> Self {
+        iter.cloned().sum()
+    }
+}
+
+impl Ord for SnailfishNumber {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.magnitude()
+            .cmp(&other.magnitude())
+            .then_with(|| self.0.cmp(&other.0))
+    }
+}
+
+impl PartialOrd for SnailfishNumber {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Display for SnailfishNumber {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl FromStr for SnailfishNumber {
+    type Err = error::ParseError;
+
+    fn from_str(s: &str) -> StdResult<Self, Self::Err> {
+        parse_all(spaces().with(snailfish_number()), s)
+    }
+}
+
+/// What adding up snailfish numbers needs, so that it can be done with either [SnailfishNumber]s
+/// or [FlatNumber]s
+pub trait Snailfish: Clone + Add<Output = Self> + for<'a> From<&'a SnailfishNumber> {
+    fn magnitude(&self) -> usize;
+}
+
+impl Snailfish for SnailfishNumber {
+    fn magnitude(&self) -> usize {
+        SnailfishNumber::magnitude(self)
+    }
+}
+
+impl From<&SnailfishNumber> for SnailfishNumber {
+    fn from(n: &SnailfishNumber) -> Self {
+        n.clone()
+    }
+}
+
+pub fn add_all_magnitude(input: &Input) -> Option<usize> {
+    add_all_magnitude_as::<SnailfishNumber>(input)
+}
+
+pub fn add_all_magnitude_as<N: Snailfish>(input: &Input) -> Option<usize> {
+    let total = input.0.iter().map(N::from).reduce(|acc, next| acc + next);
+    total.map(|n| n.magnitude())
 }
 
 pub fn biggest_pair_sum(input: &Input) -> Option<usize> {
-    let pair_trees = &input.0;
-    pair_trees
-        .iter()
-        .flat_map(|first_pair| {
-            pair_trees.iter().filter_map(move |second_pair| {
-                if first_pair != second_pair {
-                    let added = first_pair.clone() + second_pair.clone();
+    biggest_pair_sum_as::<SnailfishNumber>(input)
+}
+
+/// Like [biggest_pair_sum], adding up `N`s
+pub fn biggest_pair_sum_as<N: Snailfish>(input: &Input) -> Option<usize> {
+    let originals = &input.0;
+    let numbers: Vec<N> = originals.iter().map(N::from).collect();
+    (0..numbers.len())
+        .flat_map(|first_idx| {
+            let numbers = &numbers;
+            (0..numbers.len()).filter_map(move |second_idx| {
+                if originals[first_idx] != originals[second_idx] {
+                    let added = numbers[first_idx].clone() + numbers[second_idx].clone();
                     Some(added.magnitude())
                 } else {
                     None
@@ -192,10 +441,68 @@ pub fn biggest_pair_sum(input: &Input) -> Option<usize> {
         .max()
 }
 
-pub fn parse(s: &str) -> StdResult<Input, easy::ParseError<&str>> {
-    let mut parser = many1(pair_tree().skip(spaces())).map(Input);
-    let (r, _) = parser.easy_parse(s)?;
-    Ok(r)
+/// The pair of different numbers with the biggest sum, by their indices in the [Input]
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub struct BiggestPairSum {
+    pub magnitude: usize,
+    pub first: usize,
+    pub second: usize,
+}
+
+/// Like [biggest_pair_sum_as], but spreading the pairs over `threads` threads (at least 1), and
+/// saying which pair won. Ties go to the pair that comes first.
+pub fn biggest_pair_sum_parallel<N>(input: &Input, threads: usize) -> Option<BiggestPairSum>
+where
+    N: Snailfish + Sync,
+{
+    let originals = &input.0;
+    let numbers: Vec<N> = originals.iter().map(N::from).collect();
+    let threads = threads.clamp(1, numbers.len().max(1));
+    let best_in_partition = |partition: usize| {
+        // Interleaving the first numbers keeps the partitions about the same size
+        (partition..numbers.len())
+            .step_by(threads)
+            .flat_map(|first| (0..numbers.len()).map(move |second| (first, second)))
+            .filter(|(first, second)| originals[*first] != originals[*second])
+            .map(|(first, second)| BiggestPairSum {
+                magnitude: (numbers[first].clone() + numbers[second].clone()).magnitude(),
+                first,
+                second,
+            })
+            .max_by(prefer_bigger_then_earlier)
+    };
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = (0..threads)
+            .map(|partition| scope.spawn(move || best_in_partition(partition)))
+            .collect();
+        handles
+            .into_iter()
+            .filter_map(|handle| handle.join().expect("Pair sum thread panicked"))
+            .max_by(prefer_bigger_then_earlier)
+    })
+}
+
+fn prefer_bigger_then_earlier(a: &BiggestPairSum, b: &BiggestPairSum) -> Ordering {
+    a.magnitude
+        .cmp(&b.magnitude)
+        .then_with(|| (b.first, b.second).cmp(&(a.first, a.second)))
+}
+
+/// Snailfish numbers to add up, written like `[1,2] + [[3,4],5]`
+pub fn parse_sum(s: &str) -> StdResult<Vec<SnailfishNumber>, error::ParseError> {
+    let number = snailfish_number().skip(spaces());
+    let parser = spaces().with(sep_by1(number, char('+').skip(spaces())));
+    parse_all(parser, s)
+}
+
+pub fn parse(s: &str) -> StdResult<Input, error::ParseError> {
+    let parser = many1(snailfish_number().skip(spaces())).map(Input);
+    parse_all(parser, s)
+}
+
+// Only pairs are snailfish numbers; a regular number on its own isn't
+fn snailfish_number<'a>() -> impl Parser<PositionedStream<'a>, Output = SnailfishNumber> {
+    look_ahead(char('[')).with(pair_tree()).map(SnailfishNumber)
 }
 
 // As this expression parser needs to be able to call itself recursively `impl Parser` can't
@@ -274,6 +581,10 @@ where
 mod tests {
     use super::*;
 
+    fn input(trees: Vec<PairTree>) -> Input {
+        Input(trees.into_iter().map(SnailfishNumber).collect())
+    }
+
     static TEST_INPUT_EXPLODE_1: &str = "[[[[[9,8],1],2],3],4]";
 
     static TEST_INPUT_MULTI: &str = "[[[0,[5,8]],[[1,7],[9,6]]],[[4,[1,2]],[[1,4],2]]]
@@ -291,7 +602,7 @@ mod tests {
     fn parse_single_test() {
         let i = parse(TEST_INPUT_EXPLODE_1).unwrap();
         assert_eq!(
-            Input(vec![PairTree::pair(
+            input(vec![PairTree::pair(
                 PairTree::pair(
                     PairTree::pair(
                         PairTree::pair(PairTree::pair(Num(9), Num(8)), Num(1)),
@@ -305,6 +616,142 @@ mod tests {
         );
     }
 
+    #[test]
+    fn snailfish_number_display_from_str_test() {
+        for s in [
+            "[1,2]",
+            "[[1,2],3]",
+            "[[[[1,3],[5,3]],[[1,3],[8,7]]],[[[4,9],[6,9]],[[8,2],[7,3]]]]",
+        ] {
+            let n: SnailfishNumber = s.parse().unwrap();
+            assert_eq!(s, n.to_string());
+        }
+        assert_eq!(
+            143,
+            "[[1,2],[[3,4],5]]"
+                .parse::<SnailfishNumber>()
+                .unwrap()
+                .magnitude()
+        );
+        assert!("[1,2".parse::<SnailfishNumber>().is_err());
+        assert!("[1,2] [3,4]".parse::<SnailfishNumber>().is_err());
+        // Not a pair
+        assert!("5".parse::<SnailfishNumber>().is_err());
+        assert!(parse("[1,2]\n5").is_err());
+        assert!(parse_sum("[1,2] + 5").is_err());
+
+        // Surrounding whitespace is fine, and counts towards where errors are
+        let n: SnailfishNumber = " \n [1,2]\n".parse().unwrap();
+        assert_eq!("[1,2]", n.to_string());
+        let e = "   [1,x]".parse::<SnailfishNumber>().unwrap_err();
+        assert_eq!((1, 7), (e.line, e.column));
+
+        let n: SnailfishNumber = "[[1,2],3]".parse().unwrap();
+        assert_eq!(
+            &PairTree::pair(PairTree::pair(Num(1), Num(2)), Num(3)),
+            n.tree()
+        );
+    }
+
+    #[test]
+    fn snailfish_number_add_sum_test() {
+        let a: SnailfishNumber = "[[[[4,3],4],4],[7,[[8,4],9]]]".parse().unwrap();
+        let b: SnailfishNumber = "[1,1]".parse().unwrap();
+        assert_eq!("[[[[0,7],4],[[7,8],[6,0]]],[8,1]]", (&a + &b).to_string());
+        assert_eq!(&a + &b, a + b);
+
+        let numbers: Vec<SnailfishNumber> = ["[1,1]", "[2,2]", "[3,3]", "[4,4]", "[5,5]", "[6,6]"]
+            .iter()
+            .map(|s| s.parse().unwrap())
+            .collect();
+        let total: Option<SnailfishNumber> = numbers.iter().sum();
+        assert_eq!(
+            Some("[[[[5,0],[7,4]],[5,5]],[6,6]]".to_string()),
+            total.map(|n| n.to_string())
+        );
+        let none: Option<SnailfishNumber> = Vec::<SnailfishNumber>::new().into_iter().sum();
+        assert_eq!(None, none);
+    }
+
+    #[test]
+    fn snailfish_number_ord_test() {
+        let parse_n = |s: &str| s.parse::<SnailfishNumber>().unwrap();
+        // Magnitudes 29 and 21
+        assert!(parse_n("[9,1]") > parse_n("[1,9]"));
+        // Both 9, but not the same number
+        let (a, b) = (parse_n("[3,0]"), parse_n("[[1,0],0]"));
+        assert_eq!(a.magnitude(), b.magnitude());
+        assert_ne!(Ordering::Equal, a.cmp(&b));
+
+        let i = parse(TEST_INPUT_MULTI).unwrap();
+        let biggest = i.0.iter().max().unwrap();
+        assert_eq!(
+            i.0.iter().map(|n| n.magnitude()).max(),
+            Some(biggest.magnitude())
+        );
+    }
+
+    #[test]
+    fn add_steps_test() {
+        let a: SnailfishNumber = "[[[[4,3],4],4],[7,[[8,4],9]]]".parse().unwrap();
+        let b: SnailfishNumber = "[1,1]".parse().unwrap();
+        let steps: Vec<String> = a.add_steps(b).map(|s| s.to_string()).collect();
+        assert_eq!(
+            vec![
+                "after addition: [[[[[4,3],4],4],[7,[[8,4],9]]],[1,1]]",
+                "after explode [4,3]: [[[[0,7],4],[7,[[8,4],9]]],[1,1]]",
+                "after explode [8,4]: [[[[0,7],4],[15,[0,13]]],[1,1]]",
+                "after split 15: [[[[0,7],4],[[7,8],[0,13]]],[1,1]]",
+                "after split 13: [[[[0,7],4],[[7,8],[0,[6,7]]]],[1,1]]",
+                "after explode [6,7]: [[[[0,7],4],[[7,8],[6,0]]],[8,1]]",
+            ],
+            steps
+        );
+    }
+
+    #[test]
+    fn add_steps_paths_test() {
+        use Side::*;
+        let a: SnailfishNumber = "[[[[4,3],4],4],[7,[[8,4],9]]]".parse().unwrap();
+        let b: SnailfishNumber = "[1,1]".parse().unwrap();
+        let actions: Vec<Action> = a.add_steps(b).map(|s| s.action).collect();
+        assert_eq!(
+            vec![
+                Action::Add,
+                Action::Explode {
+                    path: vec![Left, Left, Left, Left],
+                    pair: (4, 3)
+                },
+                Action::Explode {
+                    path: vec![Left, Right, Right, Left],
+                    pair: (8, 4)
+                },
+                Action::Split {
+                    path: vec![Left, Right, Left],
+                    number: 15
+                },
+                Action::Split {
+                    path: vec![Left, Right, Right, Right],
+                    number: 13
+                },
+                Action::Explode {
+                    path: vec![Left, Right, Right, Right],
+                    pair: (6, 7)
+                },
+            ],
+            actions
+        );
+    }
+
+    #[test]
+    fn parse_sum_test() {
+        let r = parse_sum(" [1,2] +[[3,4],5]\n+ [6,7]").unwrap();
+        assert_eq!(3, r.len());
+        assert_eq!("[[3,4],5]", r[1].to_string());
+        assert!(parse_sum("[1,2] + ").is_err());
+        assert!(parse_sum("").is_err());
+    }
+
     #[test]
     fn add_all_magnitude_test() {
         let i = parse(TEST_INPUT_MULTI).unwrap();
@@ -333,11 +780,41 @@ mod tests {
         assert_eq!(Some(4656), r)
     }
 
+    #[test]
+    fn biggest_pair_sum_parallel_test() {
+        let i = parse(TEST_INPUT_MULTI).unwrap();
+        let expected = Some(BiggestPairSum {
+            magnitude: 3993,
+            first: 8,
+            second: 0,
+        });
+        for threads in [0, 1, 3, 4, 100] {
+            assert_eq!(
+                expected,
+                biggest_pair_sum_parallel::<FlatNumber>(&i, threads)
+            );
+        }
+        assert_eq!(
+            expected,
+            biggest_pair_sum_parallel::<SnailfishNumber>(&i, 2)
+        );
+
+        let r = biggest_pair_sum_parallel::<FlatNumber>(&parse(INPUT).unwrap(), 4);
+        assert_eq!(Some(4656), r.map(|b| b.magnitude));
+
+        // The same number twice isn't a pair, and ties go to the first pair
+        let i = parse("[1,1]\n[1,1]").unwrap();
+        assert_eq!(None, biggest_pair_sum_parallel::<FlatNumber>(&i, 2));
+        let i = parse("[1,1]\n[2,2]\n[1,1]").unwrap();
+        let r = biggest_pair_sum_parallel::<FlatNumber>(&i, 2).unwrap();
+        assert_eq!((1, 0), (r.first, r.second));
+    }
+
     #[test]
     fn parse_multi_test() {
         let i = parse(TEST_INPUT_MULTI).unwrap();
         assert_eq!(
-            Input(vec![
+            input(vec![
                 PairTree::pair(
                     PairTree::pair(
                         PairTree::pair(Num(0), PairTree::pair(Num(5), Num(8))),
@@ -0,0 +1,232 @@
+//! Snailfish numbers as a flat list of their regular numbers, left to right, each tagged with how
+//! many pairs it is inside of. Reducing only ever touches neighbouring entries, so there's no
+//! tree to walk or allocate.
+
+use std::fmt::{Display, Formatter};
+use std::ops::Add;
+use std::result::Result as StdResult;
+use std::str::FromStr;
+
+use super::PairTree::{self, *};
+use super::{Snailfish, SnailfishNumber};
+use crate::error;
+
+// Pairs this deep explode
+const EXPLODE_DEPTH: usize = 5;
+
+/// Adds up the same as a [SnailfishNumber], but faster
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct FlatNumber(Vec<Regular>);
+
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+struct Regular {
+    value: usize,
+    depth: usize,
+}
+
+impl FlatNumber {
+    fn reduce(&mut self) {
+        while self.explode() || self.split() {}
+    }
+
+    // Neighbours at the same depth are the leftmost pair of regular numbers that deep: if the left
+    // one were a right child, its sibling would hold such a pair further left. The first regular
+    // number that deep isn't always in one, as unreduced numbers can nest more than 1 pair too deep.
+    fn explode(&mut self) -> bool {
+        let regulars = &mut self.0;
+        let pair_at = regulars
+            .windows(2)
+            .position(|w| w[0].depth >= EXPLODE_DEPTH && w[0].depth == w[1].depth);
+        match pair_at {
+            Some(idx) => {
+                let (left, right) = (regulars[idx], regulars[idx + 1]);
+                if idx > 0 {
+                    regulars[idx - 1].value += left.value;
+                }
+                if let Some(next) = regulars.get_mut(idx + 2) {
+                    next.value += right.value;
+                }
+                regulars[idx] = Regular {
+                    value: 0,
+                    depth: left.depth - 1,
+                };
+                regulars.remove(idx + 1);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn split(&mut self) -> bool {
+        let regulars = &mut self.0;
+        match regulars.iter().position(|r| r.value >= 10) {
+            Some(idx) => {
+                let Regular { value, depth } = regulars[idx];
+                regulars[idx] = Regular {
+                    value: value / 2,
+                    depth: depth + 1,
+                };
+                regulars.insert(
+                    idx + 1,
+                    Regular {
+                        value: value.div_ceil(2),
+                        depth: depth + 1,
+                    },
+                );
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn to_tree(&self) -> PairTree {
+        fn build(regulars: &[Regular], idx: &mut usize, depth: usize) -> PairTree {
+            let regular = regulars[*idx];
+            if regular.depth == depth {
+                *idx += 1;
+                Num(regular.value)
+            } else {
+                let left = build(regulars, idx, depth + 1);
+                let right = build(regulars, idx, depth + 1);
+                PairTree::pair(left, right)
+            }
+        }
+
+        build(&self.0, &mut 0, 0)
+    }
+}
+
+impl Snailfish for FlatNumber {
+    fn magnitude(&self) -> usize {
+        // Folds each pair of regular numbers into one regular number a level up, as soon as
+        // both halves are known
+        let mut stack: Vec<Regular> = Vec::with_capacity(self.0.len());
+        for regular in self.0.iter() {
+            let mut current = *regular;
+            while let Some(left) = stack.last() {
+                if left.depth != current.depth || current.depth == 0 {
+                    break;
+                }
+                current = Regular {
+                    value: 3 * left.value + 2 * current.value,
+                    depth: current.depth - 1,
+                };
+                stack.pop();
+            }
+            stack.push(current);
+        }
+        stack.first().map(|r| r.value).unwrap_or(0)
+    }
+}
+
+impl Add for FlatNumber {
+    type Output = FlatNumber;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        let mut regulars = self.0;
+        regulars.extend(rhs.0);
+        regulars.iter_mut().for_each(|r| r.depth += 1);
+        let mut added = FlatNumber(regulars);
+        added.reduce();
+        added
+    }
+}
+
+impl From<&SnailfishNumber> for FlatNumber {
+    fn from(n: &SnailfishNumber) -> Self {
+        fn flatten(tree: &PairTree, depth: usize, regulars: &mut Vec<Regular>) {
+            match tree {
+                Num(value) => regulars.push(Regular {
+                    value: *value,
+                    depth,
+                }),
+                Pair(left, right) => {
+                    flatten(left, depth + 1, regulars);
+                    flatten(right, depth + 1, regulars);
+                }
+            }
+        }
+
+        let mut regulars = vec![];
+        flatten(&n.0, 0, &mut regulars);
+        FlatNumber(regulars)
+    }
+}
+
+impl From<&FlatNumber> for SnailfishNumber {
+    fn from(n: &FlatNumber) -> Self {
+        SnailfishNumber(n.to_tree())
+    }
+}
+
+impl Display for FlatNumber {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.to_tree())
+    }
+}
+
+impl FromStr for FlatNumber {
+    type Err = error::ParseError;
+
+    fn from_str(s: &str) -> StdResult<Self, Self::Err> {
+        let n: SnailfishNumber = s.parse()?;
+        Ok(FlatNumber::from(&n))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{add_all_magnitude_as, biggest_pair_sum_as, parse, INPUT};
+    use super::*;
+
+    #[test]
+    fn round_trip_test() {
+        for s in [
+            "[1,2]",
+            "[[1,2],3]",
+            "[[[[1,3],[5,3]],[[1,3],[8,7]]],[[[4,9],[6,9]],[[8,2],[7,3]]]]",
+        ] {
+            let n: FlatNumber = s.parse().unwrap();
+            assert_eq!(s, n.to_string());
+            assert_eq!(s, SnailfishNumber::from(&n).to_string());
+        }
+    }
+
+    #[test]
+    fn magnitude_test() {
+        let n: FlatNumber = "[[1,2],[[3,4],5]]".parse().unwrap();
+        assert_eq!(143, n.magnitude());
+        let n: FlatNumber = "[[[[8,7],[7,7]],[[8,6],[7,7]]],[[[0,7],[6,6]],[8,7]]]"
+            .parse()
+            .unwrap();
+        assert_eq!(3488, n.magnitude());
+    }
+
+    #[test]
+    fn add_test() {
+        let a: FlatNumber = "[[[[4,3],4],4],[7,[[8,4],9]]]".parse().unwrap();
+        let b: FlatNumber = "[1,1]".parse().unwrap();
+        assert_eq!("[[[[0,7],4],[[7,8],[6,0]]],[8,1]]", (a + b).to_string());
+    }
+
+    #[test]
+    fn same_as_tree_test() {
+        let i = parse(INPUT).unwrap();
+        let added = |n: &SnailfishNumber, m: &SnailfishNumber| {
+            let flat = FlatNumber::from(n) + FlatNumber::from(m);
+            (n + m, SnailfishNumber::from(&flat))
+        };
+        for (n, m) in i.0.iter().zip(i.0.iter().skip(1)) {
+            let (tree, flat) = added(n, m);
+            assert_eq!(tree, flat);
+        }
+        // Unreduced, with a pair of regular numbers under the first regular number that deep
+        let n: SnailfishNumber = "[[[[[1,[2,3]],4],5],6],7]".parse().unwrap();
+        let m: SnailfishNumber = "[1,1]".parse().unwrap();
+        let (tree, flat) = added(&n, &m);
+        assert_eq!("[[[[6,0],[6,6]],7],[1,1]]", flat.to_string());
+        assert_eq!(tree, flat);
+        assert_eq!(Some(3524), add_all_magnitude_as::<FlatNumber>(&i));
+        assert_eq!(Some(4656), biggest_pair_sum_as::<FlatNumber>(&i));
+    }
+}
@@ -1,128 +1,256 @@
-use std::collections::{HashSet, VecDeque};
+use std::cell::OnceCell;
+use std::collections::{BTreeMap, HashSet};
+use std::result::Result as StdResult;
 
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use combine::parser::char::*;
 use combine::*;
 use itertools::Itertools;
 
 use crate::common::*;
+use crate::error;
+use crate::solution::Solution;
+
+mod align;
+pub mod export;
 
 const MIN_OVERLAPS: usize = 12;
+const DETECTION_RANGE: isize = 1000;
 pub const INPUT: &str = include_str!("../data/day_19_input");
 
-pub fn run() -> Result<()> {
-    println!("*** Day 19: Beacon Scanner ***");
-    println!("Input: {}", INPUT);
-    let input = parse(INPUT)?;
-    let map = input.build_map().context("Failed to build map")?;
-    let sol_1 = map.beacons.len();
-    println!("Solution 1: {:?}", sol_1);
-    let sol_2 = map.max_distance_between_scanners();
-    println!("Solution 2: {:?}", sol_2);
-
-    Ok(())
+pub struct Day19;
+
+impl Solution for Day19 {
+    const DAY: usize = 19;
+    const TITLE: &'static str = "Beacon Scanner";
+    const INPUT: &'static str = INPUT;
+
+    type Input = Input;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(s: &str) -> Result<Self::Input> {
+        Ok(parse(s).map_err(|e| e.on_day(Self::DAY))?)
+    }
+
+    fn part_1(input: &Self::Input) -> Result<Self::Part1> {
+        Ok(placed_map(input)?.beacons.len())
+    }
+
+    fn part_2(input: &Self::Input) -> Result<Self::Part2> {
+        placed_map(input)?
+            .max_distance_between_scanners()
+            .context("No scanners in the map")
+    }
 }
 
-#[derive(Debug, Eq, PartialEq)]
-pub struct Input(Vec<Scanner>);
+// Both parts need every scanner placed
+fn placed_map(input: &Input) -> Result<&Map> {
+    let map = input.map().context("Failed to build map")?;
+    if !map.unplaced.is_empty() {
+        bail!("Could not place scanner(s) {:?}", map.unplaced)
+    }
+    Ok(map)
+}
+
+#[derive(Debug)]
+pub struct Input {
+    scanners: Vec<Scanner>,
+    // Built with the default config by whichever part asks for it first, as it's by far the
+    // most expensive bit and both parts share it
+    map: OnceCell<Option<Map>>,
+}
+
+// Only the scanners count; whether the map has been built yet doesn't
+impl PartialEq for Input {
+    fn eq(&self, other: &Self) -> bool {
+        self.scanners == other.scanners
+    }
+}
+
+impl Eq for Input {}
 
 impl Input {
+    fn new(scanners: Vec<Scanner>) -> Input {
+        Input {
+            scanners,
+            map: OnceCell::new(),
+        }
+    }
+
+    fn map(&self) -> Option<&Map> {
+        self.map.get_or_init(|| self.build_map()).as_ref()
+    }
+
+    /// None if there are no scanners. Scanners that don't see enough of the same beacons as
+    /// any placed scanner are left out of the map, and listed in [Map::unplaced].
     pub fn build_map(&self) -> Option<Map> {
-        if let Some(first) = self.0.first() {
-            let mut map = Map {
-                scanners: vec![Point { x: 0, y: 0, z: 0 }],
-                beacons: first.beacons.clone(),
-            };
-            let mut unmapped_scanner_indices = (1..self.0.len()).collect::<VecDeque<_>>();
-            while let Some(scanner_idx) = unmapped_scanner_indices.pop_front() {
-                if let Some(scanner) = self.0.get(scanner_idx) {
-                    if !scanner.merge_into(&mut map) {
-                        unmapped_scanner_indices.push_back(scanner_idx); // Revisit
-                    }
-                }
-            }
-            Some(map)
-        } else {
-            None
+        self.build_map_with(&Config::default())
+    }
+
+    pub fn build_map_with(&self, config: &Config) -> Option<Map> {
+        align::build_map(&self.scanners, config)
+    }
+}
+
+/// How scanners get placed relative to each other
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub struct Config {
+    /// How many of the same beacons two scanners need to see to be placed relative to each other
+    pub min_overlaps: usize,
+    /// How far a scanner sees along each axis. Two scanners only get placed relative to each
+    /// other if each sees every beacon of the other's that's in range of it.
+    pub detection_range: isize,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            min_overlaps: MIN_OVERLAPS,
+            detection_range: DETECTION_RANGE,
         }
     }
 }
 
+/// Everything in the frame of the first scanner
 #[derive(Debug, Eq, PartialEq)]
 pub struct Map {
-    pub scanners: Vec<Point>,
-    beacons: HashSet<Point>,
+    /// By index
+    pub scanners: BTreeMap<usize, PlacedScanner>,
+    pub beacons: HashSet<Point>,
+    /// How each scanner other than the first got placed, in the order they were
+    pub alignments: Vec<Alignment>,
+    /// Indices of the scanners that could not be placed
+    pub unplaced: Vec<usize>,
 }
 
 impl Map {
-    fn max_distance_between_scanners(&self) -> Option<usize> {
+    /// Manhattan distance between the two placed scanners furthest apart
+    pub fn max_distance_between_scanners(&self) -> Option<usize> {
         self.scanners
-            .iter()
+            .values()
             .tuple_combinations()
             .map(|(s1, s2)| {
-                ((s1.x - s2.x).abs() + (s1.y - s2.y).abs() + (s1.z - s2.z).abs()) as usize
+                let (p1, p2) = (s1.position, s2.position);
+                ((p1.x - p2.x).abs() + (p1.y - p2.y).abs() + (p1.z - p2.z).abs()) as usize
             })
             .max()
     }
+
+    /// Indices of the scanners that were placed by aligning against the given one
+    pub fn aligned_from(&self, idx: usize) -> impl Iterator<Item = usize> + '_ {
+        self.alignments
+            .iter()
+            .filter(move |a| a.from == idx)
+            .map(|a| a.to)
+    }
+}
+
+/// Scanner `to` was placed by finding `overlaps` of the beacons it sees among those scanner
+/// `from` sees
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub struct Alignment {
+    pub from: usize,
+    pub to: usize,
+    pub overlaps: usize,
+}
+
+/// Where a scanner is in the [Map], and which way it faces
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub struct PlacedScanner {
+    /// From the scanner's header in the input
+    pub idx: usize,
+    pub position: Point,
+    /// Turns points as this scanner sees them to how the map sees them
+    pub rotation: Rotation,
+}
+
+impl PlacedScanner {
+    /// A point as seen by this scanner, as the map sees it
+    pub fn to_map(&self, p: &Point) -> Point {
+        self.position.anchor(&self.rotation.apply(p))
+    }
+
+    /// A point in the map, as this scanner would see it
+    pub fn from_map(&self, p: &Point) -> Point {
+        self.rotation.inverse().apply(&self.position.relativise(p))
+    }
 }
 
 #[derive(Debug, Eq, PartialEq, Copy, Clone, Hash, Ord, PartialOrd)]
 pub struct Point {
-    x: isize,
-    y: isize,
-    z: isize,
+    pub x: isize,
+    pub y: isize,
+    pub z: isize,
 }
 
-impl Point {
-    /// Returns different perspectives of self given a perspective index from 0-23.
-    ///
-    /// If the idx provide is higher than that range, just returns self.
-    ///
-    /// Hard-coded Because I'm an idiot and don't want to do matrix stuff or pull in a lib
-    #[allow(clippy::redundant_field_names)]
-    fn perspective_at_idx(&self, idx: usize) -> Point {
-        let Point { x, y, z } = *self;
-        match idx {
-            0 => Point { x: x, y: y, z: z },
-            1 => Point { x: x, y: z, z: -y },
-            2 => Point { x: x, y: -y, z: -z },
-            3 => Point { x: x, y: -z, z: y },
-            4 => Point { x: y, y: x, z: -z },
-            5 => Point { x: y, y: z, z: x },
-            6 => Point { x: y, y: -x, z: z },
-            7 => Point { x: y, y: -z, z: -x },
-            8 => Point { x: z, y: x, z: y },
-            9 => Point { x: z, y: y, z: -x },
-            10 => Point { x: z, y: -x, z: -y },
-            11 => Point { x: z, y: -y, z: x },
-            12 => Point { x: -x, y: y, z: -z },
-            13 => Point { x: -x, y: z, z: y },
-            14 => Point { x: -x, y: -y, z: z },
-            15 => Point {
-                x: -x,
-                y: -z,
-                z: -y,
-            },
-            16 => Point { x: -y, y: x, z: z },
-            17 => Point { x: -y, y: z, z: -x },
-            18 => Point {
-                x: -y,
-                y: -x,
-                z: -z,
-            },
-            19 => Point { x: -y, y: -z, z: x },
-            20 => Point { x: -z, y: x, z: -y },
-            21 => Point { x: -z, y: y, z: x },
-            22 => Point { x: -z, y: -x, z: y },
-            23 => Point {
-                x: -z,
-                y: -y,
-                z: -x,
-            },
-            _ => *self,
+/// One of the 24 ways a scanner can be turned, as a matrix that takes points seen one way round
+/// to the other
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Hash)]
+pub struct Rotation([[isize; 3]; 3]);
+
+impl Rotation {
+    pub const IDENTITY: Rotation = Rotation([[1, 0, 0], [0, 1, 0], [0, 0, 1]]);
+
+    /// All 24 rotations, starting with [Rotation::IDENTITY]
+    pub fn all() -> impl Iterator<Item = Rotation> {
+        // Each row picks a different axis, possibly flipped; those that don't mirror are rotations
+        (0..3usize)
+            .permutations(3)
+            .cartesian_product(0..8)
+            .map(|(axes, signs)| {
+                let mut m = [[0; 3]; 3];
+                for (row, axis) in axes.into_iter().enumerate() {
+                    m[row][axis] = if signs & (1 << row) == 0 { 1 } else { -1 };
+                }
+                Rotation(m)
+            })
+            .filter(|r| r.determinant() == 1)
+    }
+
+    pub fn apply(&self, p: &Point) -> Point {
+        let m = &self.0;
+        let v = [p.x, p.y, p.z];
+        let row = |r: usize| m[r][0] * v[0] + m[r][1] * v[1] + m[r][2] * v[2];
+        Point {
+            x: row(0),
+            y: row(1),
+            z: row(2),
         }
     }
 
+    /// `other` and then `self`
+    pub fn compose(&self, other: &Rotation) -> Rotation {
+        let (a, b) = (&self.0, &other.0);
+        let mut m = [[0; 3]; 3];
+        for (row, cells) in m.iter_mut().enumerate() {
+            for (col, cell) in cells.iter_mut().enumerate() {
+                *cell = (0..3).map(|k| a[row][k] * b[k][col]).sum();
+            }
+        }
+        Rotation(m)
+    }
+
+    // Rotation matrices are orthogonal, so the transpose undoes them
+    pub fn inverse(&self) -> Rotation {
+        let mut m = [[0; 3]; 3];
+        for (row, cells) in m.iter_mut().enumerate() {
+            for (col, cell) in cells.iter_mut().enumerate() {
+                *cell = self.0[col][row];
+            }
+        }
+        Rotation(m)
+    }
+
+    fn determinant(&self) -> isize {
+        let m = &self.0;
+        m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1])
+            - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+            + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0])
+    }
+}
+
+impl Point {
     /// Relativises the given point against self
     fn relativise(&self, p: &Point) -> Point {
         Point {
@@ -149,70 +277,24 @@ pub struct Scanner {
 }
 
 impl Scanner {
-    fn different_perspectives(&self) -> impl Iterator<Item = Scanner> + '_ {
-        (0..24).map(move |p_idx| {
-            let beacons_at_perspective = self
-                .beacons
-                .iter()
-                .map(|p| p.perspective_at_idx(p_idx))
-                .collect();
-            Scanner {
-                idx: self.idx,
-                beacons: beacons_at_perspective,
-            }
-        })
+    fn different_perspectives(&self) -> impl Iterator<Item = (Rotation, Scanner)> + '_ {
+        Rotation::all().map(move |rotation| (rotation, self.rotated(&rotation)))
     }
 
-    /// Attempts to merge this [Scanner] into the provided Map
-    ///
-    /// Returns false if there were not enough overlaps found despite rotating between all
-    /// perspectives of self and relative positions, true otherwise.
-    fn merge_into(&self, map: &mut Map) -> bool {
-        for self_perspective in self.different_perspectives() {
-            for map_anchor_beacon in map.beacons.iter() {
-                let map_beacons_relative_to_map_anchor_beacon = map
-                    .beacons
-                    .iter()
-                    .map(|p| map_anchor_beacon.relativise(p))
-                    .collect::<HashSet<_>>();
-                for self_anchor_beacon in &self_perspective.beacons {
-                    let self_beacons_relative_to_self_anchor_beacon = self_perspective
-                        .beacons
-                        .iter()
-                        .map(|p| self_anchor_beacon.relativise(p))
-                        .collect::<HashSet<_>>();
-                    let overlaps = map_beacons_relative_to_map_anchor_beacon
-                        .intersection(&self_beacons_relative_to_self_anchor_beacon)
-                        .count();
-                    if overlaps >= MIN_OVERLAPS {
-                        let self_perspective_relative_to_map_anchor =
-                            self_anchor_beacon.relativise(map_anchor_beacon);
-                        let self_beacons_anchored_to_map_anchor =
-                            self_beacons_relative_to_self_anchor_beacon
-                                .iter()
-                                .map(|p| map_anchor_beacon.anchor(p))
-                                .collect::<Vec<_>>();
-                        for beacon in self_beacons_anchored_to_map_anchor {
-                            map.beacons.insert(beacon);
-                        }
-                        map.scanners.push(self_perspective_relative_to_map_anchor);
-                        return true;
-                    }
-                }
-            }
+    fn rotated(&self, rotation: &Rotation) -> Scanner {
+        Scanner {
+            idx: self.idx,
+            beacons: self.beacons.iter().map(|p| rotation.apply(p)).collect(),
         }
-        false
     }
 }
 
 // Ugh, the double newline separator still messes with me...
 // TODO: try with pure Combine, again (https://gitter.im/Marwes/combine?at=5fde845622f12e449bfe9459)
-pub fn parse(s: &str) -> Result<Input> {
-    let split_by_newline = s.trim().split("\n\n"); // ugh.... really need to figure out how to do this with just combine...
-
-    let scanners = split_by_newline
+pub fn parse(s: &str) -> StdResult<Input, error::ParseError> {
+    let scanners = sections(s)
         .into_iter()
-        .filter_map(|scanner_str| {
+        .map(|(line, scanner_str)| {
             let scanner_header_parser = string("--- scanner ")
                 .with(usize_parser())
                 .skip(string(" ---"));
@@ -223,17 +305,16 @@ pub fn parse(s: &str) -> Result<Input> {
                 .and(isize_parser())
                 .map(|((x, y), z)| Point { x, y, z });
 
-            let mut parser = scanner_header_parser
+            let parser = scanner_header_parser
                 .skip(newline())
                 .and(sep_by1(point_parser, newline()))
                 .map(|(idx, beacons)| Scanner { idx, beacons });
 
-            let (scanner, _) = parser.easy_parse(scanner_str.trim()).ok()?;
-            Some(scanner)
+            parse_from_line(parser, scanner_str, line)
         })
-        .collect();
+        .collect::<StdResult<_, _>>()?;
 
-    Ok(Input(scanners))
+    Ok(Input::new(scanners))
 }
 
 #[cfg(test)]
@@ -380,12 +461,75 @@ mod tests {
 30,-46,-14";
 
     #[test]
-    fn different_perspectives_test() {
-        let p = Point { x: 1, y: 2, z: 3 };
+    fn rotation_group_test() {
+        let all: Vec<_> = Rotation::all().collect();
+        assert_eq!(24, all.len());
+        assert_eq!(Some(&Rotation::IDENTITY), all.first());
+        assert_eq!(24, all.iter().unique().count());
+        let images: HashSet<_> = all
+            .iter()
+            .map(|r| r.apply(&Point { x: 1, y: 2, z: 3 }))
+            .collect();
+        assert_eq!(24, images.len());
 
-        let r = (0..24).map(|idx| p.perspective_at_idx(idx));
-        let distincts: Vec<_> = r.unique().collect();
-        assert_eq!(24, distincts.len())
+        for a in all.iter() {
+            assert_eq!(1, a.determinant());
+            assert_eq!(*a, a.compose(&Rotation::IDENTITY));
+            assert_eq!(*a, Rotation::IDENTITY.compose(a));
+            assert_eq!(Rotation::IDENTITY, a.compose(&a.inverse()));
+            assert_eq!(Rotation::IDENTITY, a.inverse().compose(a));
+            for b in all.iter() {
+                // Closed under composition
+                let ab = a.compose(b);
+                assert!(all.contains(&ab));
+                assert_eq!(b.inverse().compose(&a.inverse()), ab.inverse());
+                let p = Point { x: 4, y: -5, z: 6 };
+                assert_eq!(a.apply(&b.apply(&p)), ab.apply(&p));
+                for c in all.iter().step_by(5) {
+                    assert_eq!(ab.compose(c), a.compose(&b.compose(c)));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn placed_scanner_transform_test() {
+        let i = parse(TEST_INPUT).unwrap();
+        let map = i.build_map().unwrap();
+        for placed in map.scanners.values() {
+            let scanner = i.scanners.iter().find(|s| s.idx == placed.idx).unwrap();
+            for beacon in scanner.beacons.iter() {
+                let in_map = placed.to_map(beacon);
+                assert!(map.beacons.contains(&in_map));
+                assert_eq!(*beacon, placed.from_map(&in_map));
+            }
+        }
+        // The puzzle gives scanner 1's position
+        let scanner_1 = &map.scanners[&1];
+        assert_eq!(
+            Point {
+                x: 68,
+                y: -1246,
+                z: -43
+            },
+            scanner_1.position
+        );
+
+        // Between scanner frames, by way of the map
+        let scanner_0 = &map.scanners[&0];
+        let seen_by_1 = Point {
+            x: 686,
+            y: 422,
+            z: 578,
+        };
+        assert_eq!(
+            Point {
+                x: -618,
+                y: -824,
+                z: -621
+            },
+            scanner_0.from_map(&scanner_1.to_map(&seen_by_1))
+        );
     }
 
     #[test]
@@ -812,6 +956,71 @@ mod tests {
         // assert_eq!(5, r.scanners.len());
         assert_eq!(5, r.scanners.len());
         assert_eq!(expected_beacons, r.beacons);
+        assert!(r.unplaced.is_empty());
+    }
+
+    #[test]
+    fn map_alignments_test() {
+        let i = parse(TEST_INPUT).unwrap();
+        let map = i.build_map().unwrap();
+        // The pairs of scanners the puzzle says overlap, placed outwards from scanner 0
+        let edges: Vec<_> = map.alignments.iter().map(|a| (a.from, a.to)).collect();
+        assert_eq!(vec![(0, 1), (1, 3), (1, 4), (4, 2)], edges);
+        assert!(map.alignments.iter().all(|a| a.overlaps == 12));
+        assert_eq!(vec![3, 4], map.aligned_from(1).collect::<Vec<_>>());
+        assert_eq!(
+            vec![0, 1, 2, 3, 4],
+            map.scanners.keys().copied().collect::<Vec<_>>()
+        );
+        assert_eq!(
+            Point {
+                x: -20,
+                y: -1133,
+                z: 1061
+            },
+            map.scanners[&4].position
+        );
+    }
+
+    #[test]
+    fn build_map_with_test() {
+        let i = parse(TEST_INPUT).unwrap();
+        // The example's scanners overlap by exactly 12 beacons
+        let config = Config {
+            min_overlaps: 13,
+            ..Config::default()
+        };
+        let r = i.build_map_with(&config).unwrap();
+        assert_eq!(vec![1, 2, 3, 4], r.unplaced);
+        assert_eq!(1, r.scanners.len());
+
+        // Further than the scanners really see, so they miss beacons they should have seen
+        let config = Config {
+            detection_range: 1500,
+            ..Config::default()
+        };
+        let r = i.build_map_with(&config).unwrap();
+        assert_eq!(vec![1, 2, 3, 4], r.unplaced);
+    }
+
+    #[test]
+    fn build_map_unplaced_test() {
+        // A scanner that sees none of the same beacons as the others
+        let s = format!(
+            "{}\n\n--- scanner 5 ---\n1,2,3\n-40,50,60\n700,-800,900\n",
+            TEST_INPUT.trim_end()
+        );
+        let r = parse(&s).unwrap().build_map().unwrap();
+        assert_eq!(vec![5], r.unplaced);
+        assert_eq!(5, r.scanners.len());
+        assert_eq!(79, r.beacons.len());
+
+        // Still parses fine, but neither part can be solved
+        let i = Day19::parse(&s).unwrap();
+        let e = Day19::part_1(&i).unwrap_err();
+        assert_eq!("Could not place scanner(s) [5]", e.to_string());
+        let e = Day19::part_2(&i).unwrap_err();
+        assert_eq!("Could not place scanner(s) [5]", e.to_string());
     }
 
     #[test]
@@ -826,8 +1035,11 @@ mod tests {
 8,0,7",
         )
         .unwrap();
-        let first_scanner = input.0.get(0).unwrap();
-        let views_of_first_scanner = first_scanner.different_perspectives().collect::<Vec<_>>();
+        let first_scanner = input.scanners.first().unwrap();
+        let views_of_first_scanner = first_scanner
+            .different_perspectives()
+            .map(|(_, view)| view)
+            .collect::<Vec<_>>();
 
         let i = parse(
             "--- scanner 0 ---
@@ -839,7 +1051,7 @@ mod tests {
 -8,-7,0",
         )
         .unwrap();
-        let one = i.0.get(0).unwrap();
+        let one = i.scanners.first().unwrap();
 
         assert!(views_of_first_scanner.contains(one));
 
@@ -853,7 +1065,7 @@ mod tests {
 -7,0,8",
         )
         .unwrap();
-        let one = i.0.get(0).unwrap();
+        let one = i.scanners.first().unwrap();
 
         assert!(views_of_first_scanner.contains(one));
 
@@ -867,7 +1079,7 @@ mod tests {
 7,0,8",
         )
         .unwrap();
-        let one = i.0.get(0).unwrap();
+        let one = i.scanners.first().unwrap();
 
         assert!(views_of_first_scanner.contains(one));
         let i = parse(
@@ -880,7 +1092,7 @@ mod tests {
 0,7,-8",
         )
         .unwrap();
-        let one = i.0.get(0).unwrap();
+        let one = i.scanners.first().unwrap();
 
         assert!(views_of_first_scanner.contains(one));
     }
@@ -888,7 +1100,7 @@ mod tests {
     #[test]
     fn parse_test() {
         let i = parse(TEST_INPUT).unwrap();
-        let expected = Input(vec![
+        let expected = Input::new(vec![
             Scanner {
                 idx: 0,
                 beacons: HashSet::from_iter([
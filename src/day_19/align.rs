@@ -0,0 +1,293 @@
+//! Placing scanners relative to each other using fingerprints: the squared distances between
+//! each scanner's beacons, which stay the same however a scanner is turned or moved.
+//!
+//! Two scanners that see [Config::min_overlaps] of the same beacons share at least as many
+//! distances as those beacons have pairs between them, and a beacon they both see has at least
+//! `min_overlaps - 1` of its distances in common, so only those scanners and beacons are tried.
+
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+
+use itertools::Itertools;
+
+use super::{Alignment, Config, Map, PlacedScanner, Point, Rotation, Scanner};
+
+struct Fingerprint {
+    // distance -> how many pairs of beacons are that far apart
+    distances: HashMap<isize, usize>,
+    // beacon -> distance -> how many other beacons are that far from it
+    per_beacon: HashMap<Point, HashMap<isize, usize>>,
+}
+
+impl Fingerprint {
+    fn of(scanner: &Scanner) -> Fingerprint {
+        let mut distances = HashMap::new();
+        let mut per_beacon: HashMap<Point, HashMap<isize, usize>> = HashMap::new();
+        for (a, b) in scanner.beacons.iter().tuple_combinations() {
+            let d = squared_distance(a, b);
+            *distances.entry(d).or_insert(0) += 1;
+            *per_beacon.entry(*a).or_default().entry(d).or_insert(0) += 1;
+            *per_beacon.entry(*b).or_default().entry(d).or_insert(0) += 1;
+        }
+        Fingerprint {
+            distances,
+            per_beacon,
+        }
+    }
+
+    fn shared_distances(&self, other: &Fingerprint) -> usize {
+        self.distances
+            .iter()
+            .filter_map(|(d, count)| other.distances.get(d).map(|c| *c.min(count)))
+            .sum()
+    }
+
+    // Pairs of beacons (one from each) that could be the same beacon, most likely first
+    fn candidate_anchors(&self, other: &Fingerprint, min_overlaps: usize) -> Vec<(Point, Point)> {
+        let mut candidates: Vec<_> = self
+            .per_beacon
+            .iter()
+            .flat_map(|(a, a_distances)| {
+                other.per_beacon.iter().filter_map(move |(b, b_distances)| {
+                    let shared: usize = a_distances
+                        .iter()
+                        .filter_map(|(d, count)| b_distances.get(d).map(|c| *c.min(count)))
+                        .sum();
+                    if shared + 1 >= min_overlaps {
+                        Some((shared, *a, *b))
+                    } else {
+                        None
+                    }
+                })
+            })
+            .collect();
+        candidates.sort_unstable_by(|x, y| y.cmp(x));
+        candidates.into_iter().map(|(_, a, b)| (a, b)).collect()
+    }
+}
+
+fn squared_distance(a: &Point, b: &Point) -> isize {
+    let d = a.relativise(b);
+    d.x * d.x + d.y * d.y + d.z * d.z
+}
+
+/// How `to` is turned and where it is, as seen by `from`, and how many beacons they both see, if
+/// that's enough
+fn align(
+    from: &Scanner,
+    from_fingerprint: &Fingerprint,
+    to: &Scanner,
+    to_fingerprint: &Fingerprint,
+    config: &Config,
+) -> Option<(Rotation, Point, usize)> {
+    let min_overlaps = config.min_overlaps;
+    let pairs_needed = min_overlaps * min_overlaps.saturating_sub(1) / 2;
+    if from_fingerprint.shared_distances(to_fingerprint) < pairs_needed {
+        return None;
+    }
+    let anchors = from_fingerprint.candidate_anchors(to_fingerprint, min_overlaps);
+    for (rotation, rotated) in to.different_perspectives() {
+        for (from_anchor, to_anchor) in anchors.iter() {
+            // Where `to` would be if these were the same beacon
+            let offset = rotation.apply(to_anchor).relativise(from_anchor);
+            let overlaps = rotated
+                .beacons
+                .iter()
+                .filter(|p| from.beacons.contains(&offset.anchor(p)))
+                .count();
+            if overlaps >= min_overlaps
+                && in_range_beacons_seen(from, &rotated, &offset, config.detection_range)
+            {
+                return Some((rotation, offset, overlaps));
+            }
+        }
+    }
+    None
+}
+
+/// Whether each scanner sees all of the other's beacons that are in range of it, with `to` turned
+/// and moved to where `from` would see it. Otherwise enough beacons lining up was a coincidence.
+fn in_range_beacons_seen(from: &Scanner, to: &Scanner, offset: &Point, range: isize) -> bool {
+    let in_range = |p: &Point| p.x.abs() <= range && p.y.abs() <= range && p.z.abs() <= range;
+    let to_sees_from = from
+        .beacons
+        .iter()
+        .map(|p| offset.relativise(p))
+        .filter(in_range)
+        .all(|p| to.beacons.contains(&p));
+    let from_sees_to = to
+        .beacons
+        .iter()
+        .map(|p| offset.anchor(p))
+        .filter(in_range)
+        .all(|p| from.beacons.contains(&p));
+    to_sees_from && from_sees_to
+}
+
+/// Places as many scanners as possible relative to the first, trying each pair of scanners at
+/// most once
+pub(super) fn build_map(scanners: &[Scanner], config: &Config) -> Option<Map> {
+    let first = scanners.first()?;
+    let fingerprints: Vec<_> = scanners.iter().map(Fingerprint::of).collect();
+
+    let mut placed: Vec<Option<PlacedScanner>> = vec![None; scanners.len()];
+    placed[0] = Some(PlacedScanner {
+        idx: first.idx,
+        position: Point { x: 0, y: 0, z: 0 },
+        rotation: Rotation::IDENTITY,
+    });
+    let mut map = Map {
+        scanners: BTreeMap::new(),
+        beacons: HashSet::new(),
+        alignments: vec![],
+        unplaced: vec![],
+    };
+    // Each scanner is queued once, when it gets placed, so this ends
+    let mut to_align_against = VecDeque::from([0]);
+    while let Some(from_idx) = to_align_against.pop_front() {
+        let from_placed = placed[from_idx].expect("queued scanners are placed");
+        map.scanners.insert(from_placed.idx, from_placed);
+        map.beacons.extend(
+            scanners[from_idx]
+                .beacons
+                .iter()
+                .map(|p| from_placed.to_map(p)),
+        );
+        for to_idx in 0..scanners.len() {
+            if placed[to_idx].is_some() {
+                continue;
+            }
+            let alignment = align(
+                &scanners[from_idx],
+                &fingerprints[from_idx],
+                &scanners[to_idx],
+                &fingerprints[to_idx],
+                config,
+            );
+            if let Some((rotation, offset, overlaps)) = alignment {
+                placed[to_idx] = Some(PlacedScanner {
+                    idx: scanners[to_idx].idx,
+                    position: from_placed.to_map(&offset),
+                    rotation: from_placed.rotation.compose(&rotation),
+                });
+                map.alignments.push(Alignment {
+                    from: from_placed.idx,
+                    to: scanners[to_idx].idx,
+                    overlaps,
+                });
+                to_align_against.push_back(to_idx);
+            }
+        }
+    }
+    map.unplaced = scanners
+        .iter()
+        .zip(placed.iter())
+        .filter(|(_, placed)| placed.is_none())
+        .map(|(scanner, _)| scanner.idx)
+        .collect();
+    Some(map)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scanner(idx: usize, beacons: &[(isize, isize, isize)]) -> Scanner {
+        Scanner {
+            idx,
+            beacons: beacons.iter().map(|&(x, y, z)| Point { x, y, z }).collect(),
+        }
+    }
+
+    #[test]
+    fn fingerprint_test() {
+        let s = scanner(0, &[(0, 0, 0), (1, 2, 2), (3, 0, 0)]);
+        let f = Fingerprint::of(&s);
+        assert_eq!(Some(&2), f.distances.get(&9));
+        assert_eq!(Some(&1), f.distances.get(&12));
+        assert_eq!(2, f.distances.len());
+        assert_eq!(
+            Some(&HashMap::from([(9, 2)])),
+            f.per_beacon.get(&Point { x: 0, y: 0, z: 0 })
+        );
+
+        // Turned and moved, but the same distances apart
+        let turned = scanner(1, &[(10, 10, 10), (12, 9, 12), (10, 7, 10)]);
+        assert_eq!(3, f.shared_distances(&Fingerprint::of(&turned)));
+    }
+
+    // The beacons as a scanner at `position`, turned by `rotation`, would see them
+    fn seen_from(
+        idx: usize,
+        position: &Point,
+        rotation: &Rotation,
+        beacons: &[(isize, isize, isize)],
+    ) -> Scanner {
+        let seen: Vec<_> = beacons
+            .iter()
+            .map(|&(x, y, z)| {
+                let p = rotation
+                    .inverse()
+                    .apply(&position.relativise(&Point { x, y, z }));
+                (p.x, p.y, p.z)
+            })
+            .collect();
+        scanner(idx, &seen)
+    }
+
+    fn aligned(from: &Scanner, to: &Scanner, config: &Config) -> Option<(Rotation, Point, usize)> {
+        align(
+            from,
+            &Fingerprint::of(from),
+            to,
+            &Fingerprint::of(to),
+            config,
+        )
+    }
+
+    fn shared_beacons() -> Vec<(isize, isize, isize)> {
+        (0..12).map(|i| (i * i, 3 * i - 7, 100 - i * 11)).collect()
+    }
+
+    #[test]
+    fn align_test() {
+        let beacons = shared_beacons();
+        let from = scanner(0, &beacons);
+        let rotation = Rotation::all().nth(5).unwrap();
+        let position = Point { x: 5, y: -9, z: 40 };
+        let to = seen_from(1, &position, &rotation, &beacons);
+        let config = Config::default();
+        assert_eq!(Some((rotation, position, 12)), aligned(&from, &to, &config));
+
+        // One beacon short of enough, with each seeing another too far away for the other
+        let mut from_beacons = beacons[1..].to_vec();
+        from_beacons.push((3000, 0, 0));
+        let mut to_beacons = beacons[1..].to_vec();
+        to_beacons.push((-3000, 0, 0));
+        let from = scanner(0, &from_beacons);
+        let to = seen_from(1, &position, &rotation, &to_beacons);
+        assert_eq!(None, aligned(&from, &to, &config));
+        let config = Config {
+            min_overlaps: 11,
+            ..Config::default()
+        };
+        assert_eq!(Some((rotation, position, 11)), aligned(&from, &to, &config));
+    }
+
+    #[test]
+    fn align_detection_range_test() {
+        let rotation = Rotation::all().nth(9).unwrap();
+        let position = Point { x: 5, y: -9, z: 40 };
+        let to = seen_from(1, &position, &rotation, &shared_beacons());
+        // A beacon `to` would be close enough to see, but doesn't
+        let mut beacons = shared_beacons();
+        beacons.push((500, 500, 500));
+        let from = scanner(0, &beacons);
+
+        assert_eq!(None, aligned(&from, &to, &Config::default()));
+        let config = Config {
+            detection_range: 400,
+            ..Config::default()
+        };
+        assert_eq!(Some((rotation, position, 12)), aligned(&from, &to, &config));
+    }
+}
@@ -0,0 +1,193 @@
+//! Writing a [Map] out as a point cloud, to look at in a 3D viewer. Scanners and beacons are
+//! told apart by colour in PLY, by object in OBJ and by the `kind` column in CSV.
+
+use std::io::{self, Write};
+use std::path::Path;
+
+use itertools::Itertools;
+
+use super::{Map, Point};
+
+const SCANNER_COLOUR: (u8, u8, u8) = (255, 0, 0);
+const BEACON_COLOUR: (u8, u8, u8) = (255, 255, 255);
+
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum Format {
+    /// ASCII Stanford PLY, with a colour per vertex
+    Ply,
+    /// Wavefront OBJ, with the scanners and beacons as separate objects
+    Obj,
+    /// `kind,idx,x,y,z`, with no `idx` for beacons
+    Csv,
+}
+
+impl Format {
+    /// Going by the file's extension
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Option<Format> {
+        let extension = path.as_ref().extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "ply" => Some(Format::Ply),
+            "obj" => Some(Format::Obj),
+            "csv" => Some(Format::Csv),
+            _ => None,
+        }
+    }
+}
+
+pub fn write<W: Write>(map: &Map, format: Format, w: &mut W) -> io::Result<()> {
+    match format {
+        Format::Ply => write_ply(map, w),
+        Format::Obj => write_obj(map, w),
+        Format::Csv => write_csv(map, w),
+    }
+}
+
+pub fn write_ply<W: Write>(map: &Map, w: &mut W) -> io::Result<()> {
+    writeln!(w, "ply")?;
+    writeln!(w, "format ascii 1.0")?;
+    writeln!(
+        w,
+        "comment {} scanners then {} beacons",
+        map.scanners.len(),
+        map.beacons.len()
+    )?;
+    writeln!(
+        w,
+        "element vertex {}",
+        map.scanners.len() + map.beacons.len()
+    )?;
+    for property in ["x", "y", "z"] {
+        writeln!(w, "property int {}", property)?;
+    }
+    for property in ["red", "green", "blue"] {
+        writeln!(w, "property uchar {}", property)?;
+    }
+    writeln!(w, "end_header")?;
+    let scanners = map.scanners.values().map(|s| (s.position, SCANNER_COLOUR));
+    let beacons = sorted_beacons(map).map(|p| (p, BEACON_COLOUR));
+    for (p, (r, g, b)) in scanners.chain(beacons) {
+        writeln!(w, "{} {} {} {} {} {}", p.x, p.y, p.z, r, g, b)?;
+    }
+    Ok(())
+}
+
+pub fn write_obj<W: Write>(map: &Map, w: &mut W) -> io::Result<()> {
+    let scanners: Vec<_> = map.scanners.values().map(|s| s.position).collect();
+    let beacons: Vec<_> = sorted_beacons(map).collect();
+    // Vertices are numbered from 1 across the whole file
+    let mut first_vertex = 1;
+    for (name, points) in [("scanners", scanners), ("beacons", beacons)] {
+        writeln!(w, "o {}", name)?;
+        for p in points.iter() {
+            writeln!(w, "v {} {} {}", p.x, p.y, p.z)?;
+        }
+        // Viewers tend to only show vertices that something refers to
+        if !points.is_empty() {
+            let vertices = first_vertex..first_vertex + points.len();
+            writeln!(w, "p {}", vertices.format(" "))?;
+        }
+        first_vertex += points.len();
+    }
+    Ok(())
+}
+
+pub fn write_csv<W: Write>(map: &Map, w: &mut W) -> io::Result<()> {
+    writeln!(w, "kind,idx,x,y,z")?;
+    for scanner in map.scanners.values() {
+        let p = scanner.position;
+        writeln!(w, "scanner,{},{},{},{}", scanner.idx, p.x, p.y, p.z)?;
+    }
+    for p in sorted_beacons(map) {
+        writeln!(w, "beacon,,{},{},{}", p.x, p.y, p.z)?;
+    }
+    Ok(())
+}
+
+// So that the same map always gets written the same way
+fn sorted_beacons(map: &Map) -> impl Iterator<Item = Point> {
+    let mut beacons: Vec<_> = map.beacons.iter().copied().collect();
+    beacons.sort();
+    beacons.into_iter()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeMap, HashSet};
+
+    use super::super::{PlacedScanner, Rotation};
+    use super::*;
+
+    fn map() -> Map {
+        let scanner = |idx, x, y, z| PlacedScanner {
+            idx,
+            position: Point { x, y, z },
+            rotation: Rotation::IDENTITY,
+        };
+        Map {
+            scanners: BTreeMap::from([(0, scanner(0, 0, 0, 0)), (3, scanner(3, 68, -1246, -43))]),
+            beacons: HashSet::from([Point { x: 5, y: 6, z: -7 }, Point { x: -1, y: 2, z: 3 }]),
+            alignments: vec![],
+            unplaced: vec![],
+        }
+    }
+
+    fn written(format: Format) -> String {
+        let mut out = vec![];
+        write(&map(), format, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn format_from_path_test() {
+        assert_eq!(Some(Format::Ply), Format::from_path("map.ply"));
+        assert_eq!(Some(Format::Obj), Format::from_path("out/map.OBJ"));
+        assert_eq!(Some(Format::Csv), Format::from_path("map.csv"));
+        assert_eq!(None, Format::from_path("map.txt"));
+        assert_eq!(None, Format::from_path("map"));
+    }
+
+    #[test]
+    fn write_csv_test() {
+        assert_eq!(
+            "kind,idx,x,y,z
+scanner,0,0,0,0
+scanner,3,68,-1246,-43
+beacon,,-1,2,3
+beacon,,5,6,-7
+",
+            written(Format::Csv)
+        );
+    }
+
+    #[test]
+    fn write_ply_test() {
+        let s = written(Format::Ply);
+        let (header, vertices) = s.split_once("end_header\n").unwrap();
+        assert!(header.starts_with("ply\nformat ascii 1.0\n"));
+        assert!(header.contains("element vertex 4\n"));
+        assert_eq!(
+            "0 0 0 255 0 0
+68 -1246 -43 255 0 0
+-1 2 3 255 255 255
+5 6 -7 255 255 255
+",
+            vertices
+        );
+    }
+
+    #[test]
+    fn write_obj_test() {
+        assert_eq!(
+            "o scanners
+v 0 0 0
+v 68 -1246 -43
+p 1 2
+o beacons
+v -1 2 3
+v 5 6 -7
+p 3 4
+",
+            written(Format::Obj)
+        );
+    }
+}
@@ -2,25 +2,38 @@ use std::fmt::{Display, Formatter};
 use std::result::Result as StdResult;
 
 use anyhow::Result;
-use combine::easy::{Error, Info};
 use combine::parser::char::*;
 use combine::*;
 
+use crate::common::parse_all;
+use crate::error;
+use crate::grid::{self, grid_parser, Grid};
+use crate::solution::Solution;
+
 pub const INPUT: &str = include_str!("../data/day_20_input");
 
-pub fn run() -> Result<()> {
-    println!("*** Day 20: Trench Map ***");
-    println!("Input: {}", INPUT);
-    let input = parse(INPUT)?;
-    let enhanced_twice = input.enhance(2);
-    let sol_1 = enhanced_twice.image.on_pixels();
-    println!("Solution 1: {:?}", sol_1);
+pub struct Day20;
+
+impl Solution for Day20 {
+    const DAY: usize = 20;
+    const TITLE: &'static str = "Trench Map";
+    const INPUT: &'static str = INPUT;
 
-    let enhanced_fifty = input.enhance(50);
-    let sol_2 = enhanced_fifty.image.on_pixels();
-    println!("Solution 2: {:?}", sol_2);
+    type Input = Input;
+    type Part1 = usize;
+    type Part2 = usize;
 
-    Ok(())
+    fn parse(s: &str) -> Result<Self::Input> {
+        Ok(parse(s).map_err(|e| e.on_day(Self::DAY))?)
+    }
+
+    fn part_1(input: &Self::Input) -> Result<Self::Part1> {
+        Ok(input.enhance(2).image.on_pixels())
+    }
+
+    fn part_2(input: &Self::Input) -> Result<Self::Part2> {
+        Ok(input.enhance(50).image.on_pixels())
+    }
 }
 
 #[derive(Debug, Eq, PartialEq)]
@@ -43,28 +56,19 @@ impl Input {
                     self.algorithm[511 /* 111111111 in dec */]
                 };
                 // Just make sure to get the corners
-                let rows = acc.image.rows + 4;
-                let columns = acc.image.columns + 4;
-                let mut bits = vec![vec![false; columns]; rows];
-                for (new_image_row_idx, new_image_row) in bits.iter_mut().enumerate() {
-                    for (new_image_col_idx, new_image_pixel) in new_image_row.iter_mut().enumerate()
-                    {
-                        let prev_image_row_idx = new_image_row_idx as isize - 2;
-                        let prev_image_col_idx = new_image_col_idx as isize - 2;
-                        let previous_image_coords = Coords {
-                            row: prev_image_row_idx,
-                            col: prev_image_col_idx,
-                        };
-                        let algo_idx = acc.image.get_algo_index_for_pixel_at(previous_image_coords);
-                        let algo_value = self.algorithm[algo_idx];
-                        *new_image_pixel = algo_value;
-                    }
-                }
+                let rows = acc.image.bits.rows() + 4;
+                let columns = acc.image.bits.columns() + 4;
+                let bits = Grid::from_fn(rows, columns, |grid::Coords { row, col }| {
+                    let previous_image_coords = Coords {
+                        row: row as isize - 2,
+                        col: col as isize - 2,
+                    };
+                    let algo_idx = acc.image.get_algo_index_for_pixel_at(previous_image_coords);
+                    self.algorithm[algo_idx]
+                });
                 let image = Image {
                     bits,
                     background_state,
-                    rows,
-                    columns,
                 };
                 EnhancementState { step, image }
             } else {
@@ -76,10 +80,8 @@ impl Input {
 
 #[derive(Debug, Eq, PartialEq, Clone)]
 pub struct Image {
-    bits: Vec<Vec<bool>>,
+    bits: Grid<bool>,
     background_state: bool,
-    rows: usize,
-    columns: usize,
 }
 
 struct Coords {
@@ -101,39 +103,24 @@ impl Image {
             (row + 1, col + 1),
         ]
         .map(|(r, c)| {
-            if r >= 0isize && r < self.rows as isize && c >= 0isize && c < self.columns as isize {
-                self.bits[r as usize][c as usize]
-            } else {
-                self.background_state
-            }
+            self.bits
+                .checked_coords(r, c)
+                .map(|coords| self.bits[coords])
+                .unwrap_or(self.background_state)
         });
 
         to_decimal(&idx_bits)
     }
 
     pub fn on_pixels(&self) -> usize {
-        self.bits
-            .iter()
-            .map(|row| row.iter().filter(|v| **v).count())
-            .sum()
+        self.bits.values().filter(|v| **v).count()
     }
 }
 
 impl Display for Image {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        for (row_idx, row) in self.bits.iter().enumerate() {
-            for pixel in row {
-                if *pixel {
-                    write!(f, "#")?
-                } else {
-                    write!(f, ".")?
-                }
-            }
-            if row_idx < self.rows - 1 {
-                writeln!(f)?
-            }
-        }
-        Ok(())
+        let pixels = self.bits.map(|pixel| if *pixel { '#' } else { '.' });
+        write!(f, "{}", pixels)
     }
 }
 
@@ -159,34 +146,19 @@ fn to_decimal(bits: &[bool]) -> usize {
     acc
 }
 
-pub fn parse(s: &str) -> StdResult<Input, easy::ParseError<&str>> {
-    let dots_parser = || many1(char('#').map(|_| true).or(char('.').map(|_| false)));
-
-    let image_parser = many1(dots_parser().skip(spaces())).and_then(|vecs: Vec<Vec<bool>>| {
-        if let Some(first) = vecs.get(0) {
-            let rows = vecs.len();
-            let columns = first.len();
-            if vecs.iter().all(|v| v.len() == columns) {
-                Ok(Image {
-                    bits: vecs,
-                    background_state: false,
-                    rows,
-                    columns,
-                })
-            } else {
-                Err(Error::Unexpected(Info::Static("Non-rectangular input")))
-            }
-        } else {
-            Err(Error::Unexpected(Info::Static("No input")))
-        }
+pub fn parse(s: &str) -> StdResult<Input, error::ParseError> {
+    let pixel_parser = || char('#').map(|_| true).or(char('.').map(|_| false));
+
+    let image_parser = grid_parser(pixel_parser()).map(|bits| Image {
+        bits,
+        background_state: false,
     });
 
-    let mut parser = dots_parser()
+    let parser = many1(pixel_parser())
         .skip(spaces())
         .and(image_parser)
         .map(|(algorithm, image)| Input { algorithm, image });
-    let (r, _) = parser.easy_parse(s)?;
-    Ok(r)
+    parse_all(parser, s)
 }
 
 #[cfg(test)]
@@ -250,16 +222,15 @@ mod tests {
                 true, false, false, true,
             ],
             image: Image {
-                bits: vec![
+                bits: Grid::from_rows(vec![
                     vec![true, false, false, true, false],
                     vec![true, false, false, false, false],
                     vec![true, true, false, false, true],
                     vec![false, false, true, false, false],
                     vec![false, false, true, true, true],
-                ],
-                rows: 5,
+                ])
+                .unwrap(),
                 background_state: false,
-                columns: 5,
             },
         };
         assert_eq!(expected, r);
@@ -292,7 +263,7 @@ mod tests {
         let image = r.image;
         // nether regions
 
-        let expected_background_num = to_decimal(&vec![image.background_state; 9]);
+        let expected_background_num = to_decimal(&[image.background_state; 9]);
         let r = image.get_algo_index_for_pixel_at(Coords {
             row: isize::MAX / 2,
             col: isize::MAX / 2,
@@ -0,0 +1,180 @@
+use std::fmt::{Display, Formatter};
+use std::io;
+
+use combine::easy;
+use combine::stream::position::SourcePosition;
+
+// How much of the offending line to show either side of the error position
+const SNIPPET_RADIUS: usize = 30;
+
+/// Errors that can happen anywhere in the crate
+#[derive(Debug)]
+pub enum Error {
+    /// A day's puzzle input could not be parsed
+    Parse { day: usize, error: ParseError },
+    /// A day, or selection of days, that doesn't exist
+    InvalidDays(String),
+    /// Puzzle input (or another file) could not be read
+    Io { path: String, error: io::Error },
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Parse { day, .. } => write!(f, "Failed to parse day {} input", day),
+            Error::InvalidDays(s) => write!(f, "Invalid day(s): {}", s),
+            Error::Io { path, .. } => write!(f, "Failed to read [{}]", path),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Parse { error, .. } => Some(error),
+            Error::Io { error, .. } => Some(error),
+            Error::InvalidDays(_) => None,
+        }
+    }
+}
+
+/// Where in the input parsing failed, and why.
+///
+/// Lines and columns start at 1.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    /// The offending line, shortened to just around the error if it's long
+    pub snippet: String,
+    pub message: String,
+    // chars into the snippet at which the error is
+    caret: usize,
+}
+
+impl ParseError {
+    /// Builds an error at the given line and column of `s`, where `s` starts at `first_line`
+    pub(crate) fn at(
+        s: &str,
+        first_line: usize,
+        line: usize,
+        column: usize,
+        message: impl Into<String>,
+    ) -> ParseError {
+        let offending_line = s
+            .lines()
+            .nth(line.saturating_sub(first_line))
+            .unwrap_or_default();
+        let from = column.saturating_sub(1).saturating_sub(SNIPPET_RADIUS);
+        let to = column.saturating_sub(1) + SNIPPET_RADIUS;
+        let line_len = offending_line.chars().count();
+        let mut snippet: String = offending_line.chars().skip(from).take(to - from).collect();
+        let mut caret = column.saturating_sub(1) - from;
+        if from > 0 {
+            snippet.insert_str(0, "...");
+            caret += 3;
+        }
+        if to < line_len {
+            snippet.push_str("...");
+        }
+        ParseError {
+            line,
+            column,
+            snippet,
+            message: message.into(),
+            caret,
+        }
+    }
+
+    pub(crate) fn from_errors(
+        s: &str,
+        first_line: usize,
+        errors: easy::Errors<char, &str, SourcePosition>,
+    ) -> ParseError {
+        let message = Messages(&errors.errors)
+            .to_string()
+            .lines()
+            .collect::<Vec<_>>()
+            .join("; ");
+        ParseError::at(
+            s,
+            first_line,
+            errors.position.line as usize,
+            errors.position.column as usize,
+            message,
+        )
+    }
+
+    pub(crate) fn on_day(self, day: usize) -> Error {
+        Error::Parse { day, error: self }
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "failed to parse input at line {}, column {}: {}",
+            self.line, self.column, self.message
+        )?;
+        let gutter = self.line.to_string();
+        writeln!(f, "{} | {}", gutter, self.snippet)?;
+        write!(
+            f,
+            "{} | {}^",
+            " ".repeat(gutter.len()),
+            " ".repeat(self.caret)
+        )
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+// Lets us reuse combine's grouping of "Unexpected"/"Expected" messages
+struct Messages<'a, 'b>(&'a [easy::Error<char, &'b str>]);
+
+impl<'a, 'b> Display for Messages<'a, 'b> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        easy::Error::fmt_errors(self.0, f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_error_at_test() {
+        let r = ParseError::at("1,2\n3,x\n", 1, 2, 3, "Unexpected `x`");
+        assert_eq!(2, r.line);
+        assert_eq!(3, r.column);
+        assert_eq!("3,x", r.snippet);
+        assert_eq!(
+            "failed to parse input at line 2, column 3: Unexpected `x`\n2 | 3,x\n  |   ^",
+            r.to_string()
+        );
+    }
+
+    #[test]
+    fn parse_error_at_offset_test() {
+        let r = ParseError::at("3,x", 10, 10, 3, "Unexpected `x`");
+        assert_eq!("3,x", r.snippet);
+    }
+
+    #[test]
+    fn parse_error_long_line_test() {
+        let line = format!("{}x{}", "0".repeat(100), "1".repeat(100));
+        let r = ParseError::at(&line, 1, 1, 101, "Unexpected `x`");
+        let expected_snippet = format!("...{}x{}...", "0".repeat(30), "1".repeat(29));
+        assert_eq!(expected_snippet, r.snippet);
+        assert_eq!(33, r.caret);
+    }
+
+    #[test]
+    fn error_display_test() {
+        let r = anyhow::Error::from(ParseError::at("3,x", 1, 1, 3, "Unexpected `x`").on_day(6));
+        assert_eq!("Failed to parse day 6 input", r.to_string());
+        assert!(format!("{:#}", r)
+            .starts_with("Failed to parse day 6 input: failed to parse input at line 1, column 3"));
+    }
+}
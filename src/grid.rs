@@ -0,0 +1,488 @@
+use std::fmt::{Display, Formatter};
+use std::ops::{Index, IndexMut};
+use std::result::Result as StdResult;
+
+use combine::easy::{Error, Info};
+use combine::parser::char::*;
+use combine::*;
+
+use crate::common::{parse_all, PositionedStream};
+use crate::error;
+
+// (row, col) offsets, in reading order
+const NEIGHBOURS_4: [(isize, isize); 4] = [
+    (-1, 0), // N
+    (0, -1), // W
+    (0, 1),  // E
+    (1, 0),  // S
+];
+const NEIGHBOURS_8: [(isize, isize); 8] = [
+    (-1, -1), // NW
+    (-1, 0),  // N
+    (-1, 1),  // NE
+    (0, -1),  // W
+    (0, 1),   // E
+    (1, -1),  // SW
+    (1, 0),   // S
+    (1, 1),   // SE
+];
+
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Hash, Ord, PartialOrd)]
+pub struct Coords {
+    pub row: usize,
+    pub col: usize,
+}
+
+/// Position of some [Coords] in a [Grid]'s cells when laid out flat, row by row
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub struct CoordsIdx(pub usize);
+
+/// A rectangular grid of cells, stored flat, row by row
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct Grid<T> {
+    cells: Vec<T>,
+    rows: usize,
+    columns: usize,
+}
+
+impl<T> Grid<T> {
+    /// None if there are no rows, any of them are empty, or they aren't all the same length
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Option<Grid<T>> {
+        let columns = rows.first()?.len();
+        if columns > 0 && rows.iter().all(|row| row.len() == columns) {
+            let row_count = rows.len();
+            Some(Grid {
+                cells: rows.into_iter().flatten().collect(),
+                rows: row_count,
+                columns,
+            })
+        } else {
+            None
+        }
+    }
+
+    /// If either `rows` or `columns` is 0 the grid is empty, with neither rows nor columns
+    pub fn from_fn<F>(rows: usize, columns: usize, mut f: F) -> Grid<T>
+    where
+        F: FnMut(Coords) -> T,
+    {
+        let (rows, columns) = if rows == 0 || columns == 0 {
+            (0, 0)
+        } else {
+            (rows, columns)
+        };
+        let cells = (0..rows * columns)
+            .map(|idx| {
+                f(Coords {
+                    row: idx / columns,
+                    col: idx % columns,
+                })
+            })
+            .collect();
+        Grid {
+            cells,
+            rows,
+            columns,
+        }
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn columns(&self) -> usize {
+        self.columns
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    /// The bottom right corner
+    pub fn max_coords(&self) -> Coords {
+        Coords {
+            row: self.rows.saturating_sub(1),
+            col: self.columns.saturating_sub(1),
+        }
+    }
+
+    pub fn contains(&self, Coords { row, col }: Coords) -> bool {
+        row < self.rows && col < self.columns
+    }
+
+    /// Turns possibly negative or out of bounds row and col into [Coords], if they are in the grid
+    pub fn checked_coords(&self, row: isize, col: isize) -> Option<Coords> {
+        if row >= 0 && col >= 0 && (row as usize) < self.rows && (col as usize) < self.columns {
+            Some(Coords {
+                row: row as usize,
+                col: col as usize,
+            })
+        } else {
+            None
+        }
+    }
+
+    pub fn get(&self, coords: Coords) -> Option<&T> {
+        if self.contains(coords) {
+            self.cells.get(self.to_coords_idx(coords).0)
+        } else {
+            None
+        }
+    }
+
+    pub fn get_mut(&mut self, coords: Coords) -> Option<&mut T> {
+        if self.contains(coords) {
+            let idx = self.to_coords_idx(coords);
+            self.cells.get_mut(idx.0)
+        } else {
+            None
+        }
+    }
+
+    pub fn to_coords_idx(&self, Coords { row, col }: Coords) -> CoordsIdx {
+        CoordsIdx(row * self.columns + col)
+    }
+
+    pub fn to_coords(&self, CoordsIdx(idx): CoordsIdx) -> Coords {
+        Coords {
+            row: idx / self.columns,
+            col: idx % self.columns,
+        }
+    }
+
+    /// Up, down, left and right of `coords`, without going off the grid.
+    ///
+    /// Nothing if `coords` itself is off the grid.
+    pub fn neighbours_4(&self, coords: Coords) -> impl Iterator<Item = Coords> + '_ {
+        self.neighbours(coords, &NEIGHBOURS_4)
+    }
+
+    /// As [Grid::neighbours_4], but including diagonals
+    pub fn neighbours_8(&self, coords: Coords) -> impl Iterator<Item = Coords> + '_ {
+        self.neighbours(coords, &NEIGHBOURS_8)
+    }
+
+    fn neighbours(
+        &self,
+        coords: Coords,
+        offsets: &'static [(isize, isize)],
+    ) -> impl Iterator<Item = Coords> + '_ {
+        let in_grid = self.contains(coords);
+        offsets
+            .iter()
+            .filter(move |_| in_grid)
+            .filter_map(move |(row_offset, col_offset)| {
+                self.checked_coords(
+                    coords.row as isize + row_offset,
+                    coords.col as isize + col_offset,
+                )
+            })
+    }
+
+    /// Every [Coords] in the grid, row by row
+    pub fn coords(&self) -> impl Iterator<Item = Coords> {
+        let columns = self.columns;
+        (0..self.cells.len()).map(move |idx| Coords {
+            row: idx / columns,
+            col: idx % columns,
+        })
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Coords, &T)> {
+        self.coords().zip(self.cells.iter())
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &T> {
+        self.cells.iter()
+    }
+
+    pub fn values_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.cells.iter_mut()
+    }
+
+    pub fn iter_rows(&self) -> impl Iterator<Item = &[T]> {
+        self.cells.chunks(self.columns.max(1))
+    }
+
+    /// Parses a map of chars, turning each into a cell using `f`, which returns None for chars
+    /// that aren't allowed.
+    pub fn parse_chars<F>(s: &str, f: F) -> StdResult<Grid<T>, error::ParseError>
+    where
+        F: FnMut(char) -> Option<T>,
+    {
+        parse_all(grid_parser(satisfy_map(f)), s)
+    }
+
+    pub fn map<U, F>(&self, f: F) -> Grid<U>
+    where
+        F: FnMut(&T) -> U,
+    {
+        Grid {
+            cells: self.cells.iter().map(f).collect(),
+            rows: self.rows,
+            columns: self.columns,
+        }
+    }
+}
+
+impl Grid<usize> {
+    /// Parses a map of single digits, e.g. "123\n456"
+    pub fn parse_digits(s: &str) -> StdResult<Grid<usize>, error::ParseError> {
+        parse_all(grid_parser(digit_parser()), s)
+    }
+}
+
+impl<T> Index<Coords> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, coords: Coords) -> &T {
+        match self.get(coords) {
+            Some(v) => v,
+            None => panic!(
+                "{:?} is out of bounds for a {}x{} grid",
+                coords, self.rows, self.columns
+            ),
+        }
+    }
+}
+
+impl<T> IndexMut<Coords> for Grid<T> {
+    fn index_mut(&mut self, coords: Coords) -> &mut T {
+        let (rows, columns) = (self.rows, self.columns);
+        match self.get_mut(coords) {
+            Some(v) => v,
+            None => panic!(
+                "{:?} is out of bounds for a {}x{} grid",
+                coords, rows, columns
+            ),
+        }
+    }
+}
+
+impl<T: Display> Display for Grid<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (row_idx, row) in self.iter_rows().enumerate() {
+            if row_idx > 0 {
+                writeln!(f)?;
+            }
+            for v in row {
+                write!(f, "{}", v)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// One row of cells per line, all rows the same length
+pub(crate) fn grid_parser<'a, P>(
+    cell_parser: P,
+) -> impl Parser<PositionedStream<'a>, Output = Grid<P::Output>>
+where
+    P: Parser<PositionedStream<'a>>,
+{
+    sep_end_by1(many1(cell_parser), newline()).and_then(|rows: Vec<Vec<P::Output>>| {
+        Grid::from_rows(rows).ok_or(Error::Unexpected(Info::Static(
+            "Not all rows have the same length",
+        )))
+    })
+}
+
+pub(crate) fn digit_parser<'a>() -> impl Parser<PositionedStream<'a>, Output = usize> {
+    digit().and_then(|n_s| n_s.to_string().parse::<usize>())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static TEST_INPUT: &str = "1163751742
+1381373672
+2136511328
+3694931569
+7463417111
+1319128137
+1359912421
+3125421639
+1293138521
+2311944581";
+
+    #[test]
+    fn parse_digits_test() {
+        let r = Grid::parse_digits("123\n456\n").unwrap();
+        assert_eq!(Grid::from_rows(vec![vec![1, 2, 3], vec![4, 5, 6]]), Some(r));
+        let e = Grid::parse_digits("123\n45\n").unwrap_err();
+        assert!(e.message.contains("Not all rows have the same length"));
+        assert!(Grid::parse_digits("12a").is_err());
+        // Only newlines separate rows
+        assert!(Grid::parse_digits("12 34\n56").is_err());
+        assert!(Grid::parse_digits("12\n\n34").is_err());
+    }
+
+    #[test]
+    fn parse_chars_test() {
+        let r = Grid::parse_chars("#.\n.#", |c| match c {
+            '#' => Some(true),
+            '.' => Some(false),
+            _ => None,
+        })
+        .unwrap();
+        assert_eq!(2, r.rows());
+        assert_eq!(2, r.columns());
+        assert!(r[Coords { row: 1, col: 1 }]);
+        assert!(!r[Coords { row: 1, col: 0 }]);
+    }
+
+    #[test]
+    fn from_rows_test() {
+        assert_eq!(None, Grid::<usize>::from_rows(vec![]));
+        assert_eq!(None, Grid::from_rows(vec![vec![1, 2], vec![3]]));
+        assert_eq!(None, Grid::<usize>::from_rows(vec![vec![]]));
+        assert_eq!(None, Grid::<usize>::from_rows(vec![vec![], vec![]]));
+    }
+
+    #[test]
+    fn from_fn_test() {
+        let g = Grid::from_fn(2, 3, |Coords { row, col }| row * 10 + col);
+        assert_eq!(
+            Grid::from_rows(vec![vec![0, 1, 2], vec![10, 11, 12]]),
+            Some(g)
+        );
+
+        let empty = Grid::from_fn(3, 0, |_| 0);
+        assert_eq!((0, 0), (empty.rows(), empty.columns()));
+        assert!(empty.is_empty());
+        assert_eq!(0, empty.coords().count());
+        assert_eq!(None, empty.get(Coords { row: 0, col: 0 }));
+        assert_eq!("", empty.to_string());
+        assert_eq!(Grid::from_fn(0, 4, |_| 0), empty);
+    }
+
+    #[test]
+    fn get_test() {
+        let g = Grid::parse_digits(TEST_INPUT).unwrap();
+        assert_eq!(Some(&7), g.get(Coords { row: 1, col: 8 }));
+        assert_eq!(None, g.get(Coords { row: 1, col: 10 }));
+        assert_eq!(None, g.get(Coords { row: 10, col: 0 }));
+        assert_eq!(Some(Coords { row: 9, col: 0 }), g.checked_coords(9, 0));
+        assert_eq!(None, g.checked_coords(-1, 0));
+        assert_eq!(None, g.checked_coords(0, 10));
+    }
+
+    #[test]
+    fn to_coords_idx_test() {
+        let g = Grid::parse_digits(TEST_INPUT).unwrap();
+        let idx = g.to_coords_idx(Coords { row: 1, col: 0 });
+        assert_eq!(CoordsIdx(10), idx);
+    }
+
+    #[test]
+    fn to_coords_test() {
+        let g = Grid::parse_digits(TEST_INPUT).unwrap();
+        let coords = g.to_coords(CoordsIdx(13));
+        assert_eq!(Coords { row: 1, col: 3 }, coords);
+    }
+
+    #[test]
+    fn neighbours_4_test() {
+        let g = Grid::parse_digits(TEST_INPUT).unwrap();
+        let n = |row, col| g.neighbours_4(Coords { row, col }).collect::<Vec<_>>();
+        // top row
+        assert_eq!(
+            vec![Coords { row: 0, col: 1 }, Coords { row: 1, col: 0 }],
+            n(0, 0)
+        );
+        assert_eq!(
+            vec![
+                Coords { row: 0, col: 1 },
+                Coords { row: 0, col: 3 },
+                Coords { row: 1, col: 2 },
+            ],
+            n(0, 2)
+        );
+        assert_eq!(
+            vec![Coords { row: 0, col: 8 }, Coords { row: 1, col: 9 }],
+            n(0, 9)
+        );
+        // bottom row
+        assert_eq!(
+            vec![Coords { row: 8, col: 0 }, Coords { row: 9, col: 1 }],
+            n(9, 0)
+        );
+        assert_eq!(
+            vec![Coords { row: 8, col: 9 }, Coords { row: 9, col: 8 }],
+            n(9, 9)
+        );
+        // middle
+        assert_eq!(
+            vec![
+                Coords { row: 0, col: 3 },
+                Coords { row: 1, col: 2 },
+                Coords { row: 1, col: 4 },
+                Coords { row: 2, col: 3 },
+            ],
+            n(1, 3)
+        );
+        assert_eq!(
+            vec![
+                Coords { row: 1, col: 9 },
+                Coords { row: 2, col: 8 },
+                Coords { row: 3, col: 9 },
+            ],
+            n(2, 9)
+        );
+        // out of bounds
+        assert!(n(10, 10).is_empty());
+    }
+
+    #[test]
+    fn neighbours_8_test() {
+        let g = Grid::parse_digits(TEST_INPUT).unwrap();
+        let n = |row, col| g.neighbours_8(Coords { row, col }).collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                Coords { row: 0, col: 1 },
+                Coords { row: 1, col: 0 },
+                Coords { row: 1, col: 1 }
+            ],
+            n(0, 0)
+        );
+        assert_eq!(
+            vec![
+                Coords { row: 8, col: 4 },
+                Coords { row: 8, col: 5 },
+                Coords { row: 8, col: 6 },
+                Coords { row: 9, col: 4 },
+                Coords { row: 9, col: 6 }
+            ],
+            n(9, 5)
+        );
+        assert_eq!(
+            vec![
+                Coords { row: 0, col: 2 },
+                Coords { row: 0, col: 3 },
+                Coords { row: 0, col: 4 },
+                Coords { row: 1, col: 2 },
+                Coords { row: 1, col: 4 },
+                Coords { row: 2, col: 2 },
+                Coords { row: 2, col: 3 },
+                Coords { row: 2, col: 4 }
+            ],
+            n(1, 3)
+        );
+        assert!(n(10, 10).is_empty());
+    }
+
+    #[test]
+    fn display_test() {
+        let g = Grid::parse_digits("123\n456").unwrap();
+        assert_eq!("123\n456", g.to_string());
+        assert_eq!(
+            "#..\n.#.",
+            g.map(|v| if v % 4 == 1 { '#' } else { '.' }).to_string()
+        );
+    }
+}
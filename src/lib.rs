@@ -25,3 +25,8 @@ pub mod day_17;
 pub mod day_18;
 pub mod day_19;
 pub mod day_20;
+pub mod error;
+pub mod grid;
+pub mod search;
+pub mod solution;
+pub mod verify;
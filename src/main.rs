@@ -1,10 +1,15 @@
-use std::fmt::Display;
-use std::str::FromStr;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::time::Duration;
 
-use anyhow::Result;
+use anyhow::{bail, Context, Result};
 use clap::{App, Arg, ArgMatches};
 
-use aoc_2021::*;
+use aoc_2021::day_18;
+use aoc_2021::day_19::{self, export};
+use aoc_2021::error::Error;
+use aoc_2021::solution::{self, Registered, Report, Timed};
+use aoc_2021::verify::{ExpectedAnswers, VerifiedSummary};
 
 fn main() -> Result<()> {
     let matches = App::new("Advent of Code 2021")
@@ -12,38 +17,311 @@ fn main() -> Result<()> {
         .about("Solutions to AoC 2021 !")
         .arg(
             Arg::with_name("day")
-                .required(true)
+                .required_unless_one(&["snailfish-trace", "beacon-map"])
                 .takes_value(true)
                 .index(1)
-                .help("Which day's solution you want to run"),
+                .help("Which day's solution you want to run: a day like 5, a range like 1..=10, or all"),
+        )
+        .arg(
+            Arg::with_name("input")
+                .short("i")
+                .long("input")
+                .takes_value(true)
+                .value_name("PATH")
+                .help("Path to a puzzle input file, or - to read from stdin. Defaults to the embedded input"),
+        )
+        .arg(
+            Arg::with_name("format")
+                .short("f")
+                .long("format")
+                .takes_value(true)
+                .possible_values(&["text", "json"])
+                .default_value("text")
+                .help("Output format. json prints one document per day, one per line"),
+        )
+        .arg(
+            Arg::with_name("verify")
+                .long("verify")
+                .takes_value(true)
+                .value_name("EXPECTED")
+                .help("Path to a JSON file of expected answers to check results against, e.g. data/expected_answers.json"),
+        )
+        .arg(
+            Arg::with_name("snailfish-trace")
+                .long("snailfish-trace")
+                .takes_value(true)
+                .value_name("SUM")
+                .conflicts_with_all(&["day", "input", "verify"])
+                .help("Print each step of adding up snailfish numbers (day 18), e.g. \"[[[[4,3],4],4],[7,[[8,4],9]]] + [1,1]\""),
+        )
+        .arg(
+            Arg::with_name("beacon-map")
+                .long("beacon-map")
+                .takes_value(true)
+                .value_name("PATH")
+                .conflicts_with_all(&["day", "verify", "snailfish-trace"])
+                .help("Write the scanners and beacons found on day 19 to a .ply, .obj or .csv file, using --input if given"),
         )
         .get_matches();
-    match get_number("day", Some(0), &matches) {
-        1 => day_01::run()?,
-        2 => day_02::run()?,
-        3 => day_03::run()?,
-        4 => day_04::run()?,
-        5 => day_05::run()?,
-        6 => day_06::run()?,
-        7 => day_07::run()?,
-        8 => day_08::run()?,
-        9 => day_09::run()?,
-        10 => day_10::run()?,
-        11 => day_11::run()?,
-        12 => day_12::run()?,
-        13 => day_13::run()?,
-        14 => day_14::run()?,
-        15 => day_15::run()?,
-        16 => day_16::run()?,
-        17 => day_17::run()?,
-        18 => day_18::run()?,
-        19 => day_19::run()?,
-        20 => day_20::run()?,
-        other => anyhow::bail!(format!("Invalid day: {}", other)),
+    if let Some(sum) = matches.value_of("snailfish-trace") {
+        return print_snailfish_trace(sum);
+    }
+    let custom_input = read_input(&matches)?;
+    if let Some(path) = matches.value_of("beacon-map") {
+        let input = custom_input.as_deref().unwrap_or(day_19::INPUT);
+        return export_beacon_map(input, path);
+    }
+    let days = solution::select(matches.value_of("day").unwrap_or_default())?;
+
+    if days.len() > 1 && custom_input.is_some() {
+        bail!("--input can only be used when running a single day");
+    }
+
+    if let Some(expected_path) = matches.value_of("verify") {
+        let expected_s = fs::read_to_string(expected_path).map_err(|error| Error::Io {
+            path: expected_path.to_string(),
+            error,
+        })?;
+        let expected = ExpectedAnswers::parse(&expected_s)?;
+        let json = matches.value_of("format") == Some("json");
+        let mut failures = 0;
+        for solution in days {
+            let input = custom_input.as_deref().unwrap_or(solution.input);
+            let report = solution.run(input);
+            let checks = expected.check(solution, &report);
+            failures += checks.iter().filter(|c| c.verdict.is_failure()).count();
+            if json {
+                let verified = VerifiedSummary {
+                    summary: report.summarise(solution),
+                    checks,
+                };
+                println!("{}", serde_json::to_string(&verified)?);
+            } else {
+                for check in checks {
+                    println!("{}", check);
+                }
+            }
+        }
+        if failures > 0 {
+            bail!("{} part(s) did not match the expected answers", failures);
+        }
+        return Ok(());
+    }
+
+    if matches.value_of("format") == Some("json") {
+        let mut failures = 0;
+        for solution in days {
+            let input = custom_input.as_deref().unwrap_or(solution.input);
+            let report = solution.run(input);
+            if report.has_errors() {
+                failures += 1;
+            }
+            println!("{}", serde_json::to_string(&report.summarise(solution))?);
+        }
+        if failures > 0 {
+            bail!("{} day(s) failed", failures);
+        }
+        return Ok(());
+    }
+
+    match days.as_slice() {
+        [solution] => {
+            let input = custom_input.as_deref().unwrap_or(solution.input);
+            println!("*** Day {}: {} ***", solution.day, solution.title);
+            println!("Input: {}", input);
+            let answers = solution.solve(input)?;
+            print_answer(1, &answers.part_1);
+            print_answer(2, &answers.part_2);
+        }
+        _ => {
+            let reports: Vec<_> = days
+                .into_iter()
+                .map(|solution| (solution, solution.run(solution.input)))
+                .collect();
+            print_table(&reports);
+            let failures = reports
+                .iter()
+                .filter(|(_, report)| report.has_errors())
+                .count();
+            if failures > 0 {
+                bail!("{} day(s) failed", failures);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn print_table(reports: &[(&Registered, Report)]) {
+    let header = [
+        "Day",
+        "Title",
+        "Part 1",
+        "Part 2",
+        "Parse time",
+        "Part 1 time",
+        "Part 2 time",
+        "Total time",
+    ]
+    .iter()
+    .map(|h| h.to_string())
+    .collect::<Vec<_>>();
+    let mut rows = vec![header];
+    for (solution, report) in reports {
+        let (part_1, part_2) = match &report.parse.value {
+            Err(e) => (format!("parse error: {}", first_line(e)), "-".to_string()),
+            Ok(_) => (answer_cell(&report.part_1), answer_cell(&report.part_2)),
+        };
+        rows.push(vec![
+            solution.day.to_string(),
+            solution.title.to_string(),
+            part_1,
+            part_2,
+            format_duration(Some(report.parse.elapsed)),
+            format_duration(report.part_1.as_ref().map(|t| t.elapsed)),
+            format_duration(report.part_2.as_ref().map(|t| t.elapsed)),
+            format_duration(Some(report.total_elapsed())),
+        ]);
+    }
+    let total: Duration = reports.iter().map(|(_, r)| r.total_elapsed()).sum();
+    let mut footer = vec![String::new(); 7];
+    footer.push(format_duration(Some(total)));
+    rows.push(footer);
+
+    let widths: Vec<usize> = (0..rows[0].len())
+        .map(|col| {
+            rows.iter()
+                .map(|row| row[col].chars().count())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+    let last_row_idx = rows.len() - 1;
+    for (row_idx, row) in rows.iter().enumerate() {
+        if row_idx == last_row_idx {
+            print_separator(&widths);
+        }
+        let cells: Vec<String> = row
+            .iter()
+            .zip(widths.iter())
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect();
+        println!("{}", cells.join(" | ").trim_end());
+        if row_idx == 0 {
+            print_separator(&widths);
+        }
+    }
+}
+
+fn print_separator(widths: &[usize]) {
+    let dashes: Vec<String> = widths.iter().map(|w| "-".repeat(*w)).collect();
+    println!("{}", dashes.join("-+-"));
+}
+
+fn answer_or_error(part: &Option<Timed<Result<String>>>) -> Result<&str, String> {
+    match part {
+        Some(Timed { value: Ok(a), .. }) => Ok(a),
+        Some(Timed { value: Err(e), .. }) => Err(format!("error: {}", first_line(e))),
+        None => Err("-".to_string()),
+    }
+}
+
+fn answer_cell(part: &Option<Timed<Result<String>>>) -> String {
+    match answer_or_error(part) {
+        Ok(a) if a.contains('\n') => format!("[{} lines]", a.lines().count()),
+        Ok(a) => a.to_string(),
+        Err(e) => e,
+    }
+}
+
+fn first_line(e: &anyhow::Error) -> String {
+    format!("{:#}", e)
+        .lines()
+        .next()
+        .unwrap_or_default()
+        .to_string()
+}
+
+fn format_duration(d: Option<Duration>) -> String {
+    d.map(|d| format!("{:.2?}", d))
+        .unwrap_or_else(|| "-".to_string())
+}
+
+// Multi-line answers (e.g. letters drawn on a grid) read better starting on their own line
+fn print_answer(part: usize, answer: &str) {
+    if answer.contains('\n') {
+        println!("Solution {}:\n{}\n", part, answer);
+    } else {
+        println!("Solution {}: {}\n", part, answer);
+    }
+}
+
+fn print_snailfish_trace(sum: &str) -> Result<()> {
+    let mut numbers = day_18::parse_sum(sum)?.into_iter();
+    if let Some(mut total) = numbers.next() {
+        println!("{}", total);
+        for next in numbers {
+            println!("+ {}", next);
+            for step in total.clone().add_steps(next) {
+                println!("  {}", step);
+                total = step.number;
+            }
+        }
+        println!("= {}", total);
+        println!("Magnitude: {}", total.magnitude());
     }
     Ok(())
 }
 
+fn export_beacon_map(input: &str, path: &str) -> Result<()> {
+    let format = export::Format::from_path(path).with_context(|| {
+        format!(
+            "Unknown export format for [{}]; use .ply, .obj or .csv",
+            path
+        )
+    })?;
+    let map = day_19::parse(input)?
+        .build_map()
+        .context("No scanners in the input")?;
+    if !map.unplaced.is_empty() {
+        eprintln!("Could not place scanner(s) {:?}", map.unplaced);
+    }
+    let file = fs::File::create(path).with_context(|| format!("Failed to write [{}]", path))?;
+    let mut file = io::BufWriter::new(file);
+    export::write(&map, format, &mut file)
+        .and_then(|_| file.flush())
+        .with_context(|| format!("Failed to write [{}]", path))?;
+    println!(
+        "Wrote {} scanners and {} beacons to [{}]",
+        map.scanners.len(),
+        map.beacons.len(),
+        path
+    );
+    Ok(())
+}
+
+fn read_input(matches: &ArgMatches) -> Result<Option<String>> {
+    match matches.value_of("input") {
+        Some("-") => {
+            let mut s = String::new();
+            io::stdin()
+                .read_to_string(&mut s)
+                .map_err(|error| Error::Io {
+                    path: "-".to_string(),
+                    error,
+                })?;
+            Ok(Some(s))
+        }
+        Some(path) => {
+            let s = fs::read_to_string(path).map_err(|error| Error::Io {
+                path: path.to_string(),
+                error,
+            })?;
+            Ok(Some(s))
+        }
+        None => Ok(None),
+    }
+}
+
 fn version() -> String {
     let (maj, min, pat) = (
         option_env!("CARGO_PKG_VERSION_MAJOR"),
@@ -55,32 +333,3 @@ fn version() -> String {
         _ => "".to_owned(),
     }
 }
-
-fn get_number<'a, A>(name: &str, maybe_min: Option<A>, matches: &ArgMatches<'a>) -> A
-where
-    A: FromStr + PartialOrd + Display + Copy,
-    <A as FromStr>::Err: std::fmt::Debug,
-{
-    matches
-        .value_of(name)
-        .and_then(|s| s.parse::<A>().ok())
-        .and_then(|u| match maybe_min {
-            Some(min) => {
-                if u > min {
-                    Some(u)
-                } else {
-                    None
-                }
-            }
-            _ => Some(u),
-        })
-        .expect(
-            &{
-                if let Some(min) = maybe_min {
-                    format!("{} should be a positive number greater than {}.", name, min)
-                } else {
-                    format!("{} should be a positive number.", name)
-                }
-            }[..],
-        )
-}
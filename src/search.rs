@@ -0,0 +1,255 @@
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+use std::hash::Hash;
+
+/// The nodes visited going from a search's start to its goal (both included), and what it cost
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct Path<N> {
+    pub cost: usize,
+    pub nodes: Vec<N>,
+}
+
+/// Cheapest path from `start` to a node for which `is_goal` is true, where `neighbours` gives the
+/// nodes reachable from a node along with the cost of moving to each.
+pub fn dijkstra<N, FN, IN, FG>(start: N, neighbours: FN, is_goal: FG) -> Option<Path<N>>
+where
+    N: Eq + Hash + Clone,
+    FN: FnMut(&N) -> IN,
+    IN: IntoIterator<Item = (N, usize)>,
+    FG: FnMut(&N) -> bool,
+{
+    astar(start, neighbours, |_| 0, is_goal)
+}
+
+/// As [dijkstra], but exploring nodes that `heuristic` estimates to be closer to the goal first.
+///
+/// The heuristic must never overestimate the remaining cost, otherwise the path found might not
+/// be the cheapest.
+pub fn astar<N, FN, IN, FH, FG>(
+    start: N,
+    mut neighbours: FN,
+    mut heuristic: FH,
+    mut is_goal: FG,
+) -> Option<Path<N>>
+where
+    N: Eq + Hash + Clone,
+    FN: FnMut(&N) -> IN,
+    IN: IntoIterator<Item = (N, usize)>,
+    FH: FnMut(&N) -> usize,
+    FG: FnMut(&N) -> bool,
+{
+    // node -> (lowest known cost from `start`, the node we got there from)
+    let mut best_known: HashMap<N, (usize, Option<N>)> = HashMap::new();
+    let mut to_visit_prioritised_q = BinaryHeap::new();
+
+    best_known.insert(start.clone(), (0, None));
+    to_visit_prioritised_q.push(Visit {
+        estimate: heuristic(&start),
+        cost: 0,
+        node: start,
+    });
+
+    // Examine the frontier with the most promising nodes first
+    while let Some(Visit { cost, node, .. }) = to_visit_prioritised_q.pop() {
+        if is_goal(&node) {
+            return Some(Path {
+                cost,
+                nodes: reconstruct(&best_known, node),
+            });
+        }
+        if best_known
+            .get(&node)
+            .is_some_and(|(known, _)| cost > *known)
+        {
+            // Skip exploring this path; we already know of a cheaper way of getting here
+            continue;
+        }
+        for (next, step_cost) in neighbours(&node) {
+            let next_cost = cost + step_cost;
+            let cheaper = best_known
+                .get(&next)
+                .is_none_or(|(known, _)| next_cost < *known);
+            if cheaper {
+                best_known.insert(next.clone(), (next_cost, Some(node.clone())));
+                to_visit_prioritised_q.push(Visit {
+                    estimate: next_cost + heuristic(&next),
+                    cost: next_cost,
+                    node: next,
+                });
+            }
+        }
+    }
+
+    // Goal not reachable
+    None
+}
+
+/// Path with the fewest steps from `start` to a node for which `is_goal` is true. The cost is
+/// the number of steps.
+pub fn bfs<N, FN, IN, FG>(start: N, mut neighbours: FN, mut is_goal: FG) -> Option<Path<N>>
+where
+    N: Eq + Hash + Clone,
+    FN: FnMut(&N) -> IN,
+    IN: IntoIterator<Item = N>,
+    FG: FnMut(&N) -> bool,
+{
+    let mut came_from: HashMap<N, (usize, Option<N>)> = HashMap::new();
+    let mut to_visit = VecDeque::new();
+    came_from.insert(start.clone(), (0, None));
+    to_visit.push_back((start, 0));
+    while let Some((node, steps)) = to_visit.pop_front() {
+        if is_goal(&node) {
+            return Some(Path {
+                cost: steps,
+                nodes: reconstruct(&came_from, node),
+            });
+        }
+        for next in neighbours(&node) {
+            if !came_from.contains_key(&next) {
+                came_from.insert(next.clone(), (steps + 1, Some(node.clone())));
+                to_visit.push_back((next, steps + 1));
+            }
+        }
+    }
+    None
+}
+
+/// Every node reachable from `start`, including `start`
+pub fn flood_fill<N, FN, IN>(start: N, mut neighbours: FN) -> HashSet<N>
+where
+    N: Eq + Hash + Clone,
+    FN: FnMut(&N) -> IN,
+    IN: IntoIterator<Item = N>,
+{
+    let mut filled = HashSet::new();
+    let mut to_visit = vec![start];
+    while let Some(node) = to_visit.pop() {
+        if filled.insert(node.clone()) {
+            to_visit.extend(
+                neighbours(&node)
+                    .into_iter()
+                    .filter(|n| !filled.contains(n)),
+            );
+        }
+    }
+    filled
+}
+
+fn reconstruct<N>(came_from: &HashMap<N, (usize, Option<N>)>, goal: N) -> Vec<N>
+where
+    N: Eq + Hash + Clone,
+{
+    let mut nodes = vec![goal];
+    while let Some((_, Some(previous))) = nodes.last().and_then(|n| came_from.get(n)) {
+        nodes.push(previous.clone());
+    }
+    nodes.reverse();
+    nodes
+}
+
+struct Visit<N> {
+    estimate: usize,
+    cost: usize,
+    node: N,
+}
+
+// The priority queue depends on `Ord`, which we flip so that it becomes a min-heap on the
+// estimated total cost. Nodes don't take part in the ordering, so neither can they in equality.
+impl<N> Ord for Visit<N> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .estimate
+            .cmp(&self.estimate)
+            .then_with(|| self.cost.cmp(&other.cost))
+    }
+}
+
+impl<N> PartialOrd for Visit<N> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<N> PartialEq for Visit<N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<N> Eq for Visit<N> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 0 -1-> 1 -1-> 2 -1-> 3, with a pricey shortcut 0 -5-> 3 and a dead end at 4
+    fn weighted_neighbours(n: &usize) -> Vec<(usize, usize)> {
+        match n {
+            0 => vec![(1, 1), (3, 5)],
+            1 => vec![(2, 1)],
+            2 => vec![(3, 1)],
+            _ => vec![],
+        }
+    }
+
+    #[test]
+    fn dijkstra_test() {
+        let r = dijkstra(0, weighted_neighbours, |n| *n == 3);
+        assert_eq!(
+            Some(Path {
+                cost: 3,
+                nodes: vec![0, 1, 2, 3]
+            }),
+            r
+        );
+        assert_eq!(None, dijkstra(0, weighted_neighbours, |n| *n == 4));
+        let r = dijkstra(2, weighted_neighbours, |n| *n == 2);
+        assert_eq!(
+            Some(Path {
+                cost: 0,
+                nodes: vec![2]
+            }),
+            r
+        );
+    }
+
+    #[test]
+    fn astar_test() {
+        // Walking along a line of numbers, where each step costs the number stepped on
+        let neighbours = |n: &isize| {
+            vec![
+                (n - 1, (n - 1).unsigned_abs()),
+                (n + 1, (n + 1).unsigned_abs()),
+            ]
+        };
+        let heuristic = |n: &isize| (7 - n).unsigned_abs();
+        let r = astar(3, neighbours, heuristic, |n| *n == 7).unwrap();
+        assert_eq!(4 + 5 + 6 + 7, r.cost);
+        assert_eq!(vec![3, 4, 5, 6, 7], r.nodes);
+        assert_eq!(Some(r), dijkstra(3, neighbours, |n| *n == 7));
+    }
+
+    #[test]
+    fn bfs_test() {
+        let neighbours = |n: &usize| weighted_neighbours(n).into_iter().map(|(n, _)| n);
+        let r = bfs(0, neighbours, |n| *n == 3);
+        assert_eq!(
+            Some(Path {
+                cost: 1,
+                nodes: vec![0, 3]
+            }),
+            r
+        );
+        assert_eq!(None, bfs(0, neighbours, |n| *n == 4));
+    }
+
+    #[test]
+    fn flood_fill_test() {
+        let neighbours = |n: &usize| weighted_neighbours(n).into_iter().map(|(n, _)| n);
+        let r = flood_fill(1, neighbours);
+        assert_eq!(HashSet::from([1, 2, 3]), r);
+        // Cycles are fine
+        let r = flood_fill(0, |n: &usize| vec![(n + 1) % 4]);
+        assert_eq!(HashSet::from([0, 1, 2, 3]), r);
+    }
+}
@@ -0,0 +1,346 @@
+use std::fmt::Display;
+use std::result::Result as StdResult;
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+
+use crate::error::Error;
+use crate::*;
+
+/// A single day's puzzle: how to parse its input, and how to solve each part given that.
+pub trait Solution {
+    const DAY: usize;
+    const TITLE: &'static str;
+    /// The puzzle input embedded at compile time, used when no other input is provided
+    const INPUT: &'static str;
+
+    type Input;
+    type Part1: Display;
+    type Part2: Display;
+
+    /// Only parsing goes here, so that the parse and part timings in reports mean what they say.
+    /// Work both parts share can be done by whichever part needs it first, as day 19 does.
+    fn parse(s: &str) -> Result<Self::Input>;
+    fn part_1(input: &Self::Input) -> Result<Self::Part1>;
+    fn part_2(input: &Self::Input) -> Result<Self::Part2>;
+}
+
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct Answers {
+    pub part_1: String,
+    pub part_2: String,
+}
+
+#[derive(Debug)]
+pub struct Timed<T> {
+    pub value: T,
+    pub elapsed: Duration,
+}
+
+impl<T> Timed<T> {
+    fn measure<F>(f: F) -> Timed<T>
+    where
+        F: FnOnce() -> T,
+    {
+        let start = Instant::now();
+        let value = f();
+        Timed {
+            value,
+            elapsed: start.elapsed(),
+        }
+    }
+}
+
+/// What happened when running a day: each step's result along with how long it took.
+///
+/// The parts are only attempted if parsing succeeded.
+#[derive(Debug)]
+pub struct Report {
+    pub parse: Timed<Result<()>>,
+    pub part_1: Option<Timed<Result<String>>>,
+    pub part_2: Option<Timed<Result<String>>>,
+}
+
+impl Report {
+    pub fn total_elapsed(&self) -> Duration {
+        self.parse.elapsed
+            + self.part_1.as_ref().map(|t| t.elapsed).unwrap_or_default()
+            + self.part_2.as_ref().map(|t| t.elapsed).unwrap_or_default()
+    }
+
+    pub fn into_answers(self) -> Result<Answers> {
+        self.parse.value?;
+        let part_1 = self.part_1.context("Part 1 was not run")?.value?;
+        let part_2 = self.part_2.context("Part 2 was not run")?.value?;
+        Ok(Answers { part_1, part_2 })
+    }
+}
+
+/// A [Report] flattened into plain data, e.g. for serialising to JSON
+#[derive(Debug, Eq, PartialEq, Serialize)]
+pub struct Summary {
+    pub day: usize,
+    pub title: &'static str,
+    pub parse: StepSummary,
+    pub part_1: Option<StepSummary>,
+    pub part_2: Option<StepSummary>,
+    pub elapsed_nanos: u128,
+}
+
+#[derive(Debug, Eq, PartialEq, Serialize)]
+pub struct StepSummary {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub answer: Option<String>,
+    pub error: Option<String>,
+    pub elapsed_nanos: u128,
+}
+
+impl StepSummary {
+    fn of<T, F>(step: &Timed<Result<T>>, to_answer: F) -> StepSummary
+    where
+        F: FnOnce(&T) -> Option<String>,
+    {
+        let (answer, error) = match &step.value {
+            Ok(v) => (to_answer(v), None),
+            Err(e) => (None, Some(format!("{:#}", e))),
+        };
+        StepSummary {
+            answer,
+            error,
+            elapsed_nanos: step.elapsed.as_nanos(),
+        }
+    }
+}
+
+impl Report {
+    pub fn summarise(&self, solution: &Registered) -> Summary {
+        let part = |p: &Option<Timed<Result<String>>>| {
+            p.as_ref().map(|p| StepSummary::of(p, |a| Some(a.clone())))
+        };
+        Summary {
+            day: solution.day,
+            title: solution.title,
+            parse: StepSummary::of(&self.parse, |_| None),
+            part_1: part(&self.part_1),
+            part_2: part(&self.part_2),
+            elapsed_nanos: self.total_elapsed().as_nanos(),
+        }
+    }
+
+    pub fn has_errors(&self) -> bool {
+        let part_failed = |p: &Option<Timed<Result<String>>>| {
+            p.as_ref().map(|p| p.value.is_err()).unwrap_or(true)
+        };
+        part_failed(&self.part_1) || part_failed(&self.part_2)
+    }
+}
+
+/// A type-erased [Solution] so that days can be enumerated and run generically.
+#[derive(Copy, Clone)]
+pub struct Registered {
+    pub day: usize,
+    pub title: &'static str,
+    pub input: &'static str,
+    run: fn(&str) -> Report,
+}
+
+impl Registered {
+    pub const fn of<S: Solution>() -> Registered {
+        Registered {
+            day: S::DAY,
+            title: S::TITLE,
+            input: S::INPUT,
+            run: run::<S>,
+        }
+    }
+
+    pub fn run(&self, input: &str) -> Report {
+        (self.run)(input)
+    }
+
+    pub fn solve(&self, input: &str) -> Result<Answers> {
+        self.run(input).into_answers()
+    }
+}
+
+fn run<S: Solution>(input: &str) -> Report {
+    let parsed = Timed::measure(|| S::parse(input));
+    match parsed.value {
+        Ok(parsed_input) => {
+            let part_1 = Timed::measure(|| S::part_1(&parsed_input).map(|a| a.to_string()));
+            let part_2 = Timed::measure(|| S::part_2(&parsed_input).map(|a| a.to_string()));
+            Report {
+                parse: Timed {
+                    value: Ok(()),
+                    elapsed: parsed.elapsed,
+                },
+                part_1: Some(part_1),
+                part_2: Some(part_2),
+            }
+        }
+        Err(e) => Report {
+            parse: Timed {
+                value: Err(e),
+                elapsed: parsed.elapsed,
+            },
+            part_1: None,
+            part_2: None,
+        },
+    }
+}
+
+/// Every day that has a [Solution], in order.
+pub static SOLUTIONS: &[Registered] = &[
+    Registered::of::<day_01::Day01>(),
+    Registered::of::<day_02::Day02>(),
+    Registered::of::<day_03::Day03>(),
+    Registered::of::<day_04::Day04>(),
+    Registered::of::<day_05::Day05>(),
+    Registered::of::<day_06::Day06>(),
+    Registered::of::<day_07::Day07>(),
+    Registered::of::<day_08::Day08>(),
+    Registered::of::<day_09::Day09>(),
+    Registered::of::<day_10::Day10>(),
+    Registered::of::<day_11::Day11>(),
+    Registered::of::<day_12::Day12>(),
+    Registered::of::<day_13::Day13>(),
+    Registered::of::<day_14::Day14>(),
+    Registered::of::<day_15::Day15>(),
+    Registered::of::<day_16::Day16>(),
+    Registered::of::<day_17::Day17>(),
+    Registered::of::<day_18::Day18>(),
+    Registered::of::<day_19::Day19>(),
+    Registered::of::<day_20::Day20>(),
+];
+
+pub fn find(day: usize) -> Option<&'static Registered> {
+    SOLUTIONS.iter().find(|r| r.day == day)
+}
+
+/// Picks out days given a selection that is either "all", a single day like "5", or a range
+/// like "1..=10" or "1..11"
+pub fn select(selection: &str) -> Result<Vec<&'static Registered>> {
+    let selection = selection.trim();
+    let (from, to) = if selection == "all" {
+        (1, usize::MAX)
+    } else if let Some((from, to)) = selection.split_once("..=") {
+        (parse_day(from)?, parse_day(to)?)
+    } else if let Some((from, to)) = selection.split_once("..") {
+        let to = parse_day(to)?;
+        (parse_day(from)?, to.saturating_sub(1))
+    } else {
+        let day = parse_day(selection)?;
+        (day, day)
+    };
+    let selected: Vec<_> = SOLUTIONS
+        .iter()
+        .filter(|r| r.day >= from && r.day <= to)
+        .collect();
+    if selected.is_empty() {
+        return Err(Error::InvalidDays(format!("no days match [{}]", selection)).into());
+    }
+    Ok(selected)
+}
+
+fn parse_day(s: &str) -> StdResult<usize, Error> {
+    match s.trim().parse() {
+        Ok(day) if find(day).is_some() => Ok(day),
+        _ => Err(Error::InvalidDays(format!(
+            "[{}] is not a day with a solution",
+            s
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn solutions_are_in_day_order_test() {
+        let days: Vec<usize> = SOLUTIONS.iter().map(|r| r.day).collect();
+        let expected: Vec<usize> = (1..=20).collect();
+        assert_eq!(expected, days);
+    }
+
+    #[test]
+    fn find_test() {
+        assert_eq!(Some(6), find(6).map(|r| r.day));
+        assert!(find(0).is_none());
+        assert!(find(26).is_none());
+    }
+
+    #[test]
+    fn select_test() {
+        let days = |selection: &str| -> Vec<usize> {
+            select(selection).unwrap().iter().map(|r| r.day).collect()
+        };
+        assert_eq!((1..=20).collect::<Vec<_>>(), days("all"));
+        assert_eq!(vec![5], days("5"));
+        assert_eq!(vec![3, 4, 5], days("3..=5"));
+        assert_eq!(vec![3, 4], days("3..5"));
+        assert!(select("0").is_err());
+        assert!(select("5..=26").is_err());
+        assert!(select("five").is_err());
+        assert!(select("5..5").is_err());
+    }
+
+    #[test]
+    fn run_test() {
+        let report = find(6).unwrap().run("3,4,3,1,2");
+        assert!(report.parse.value.is_ok());
+        assert_eq!(
+            Some("5934"),
+            report
+                .part_1
+                .as_ref()
+                .and_then(|p| p.value.as_ref().ok())
+                .map(|s| s.as_str())
+        );
+    }
+
+    #[test]
+    fn run_parse_failure_test() {
+        let report = find(6).unwrap().run("nope");
+        assert!(report.parse.value.is_err());
+        assert!(report.part_1.is_none());
+        assert!(report.part_2.is_none());
+    }
+
+    #[test]
+    fn summarise_test() {
+        let solution = find(6).unwrap();
+        let summary = solution.run("3,4,3,1,2").summarise(solution);
+        assert_eq!(6, summary.day);
+        assert_eq!("Lanternfish", summary.title);
+        assert_eq!(None, summary.parse.error);
+        assert_eq!(
+            Some("5934".to_string()),
+            summary.part_1.and_then(|p| p.answer)
+        );
+    }
+
+    #[test]
+    fn summarise_parse_failure_test() {
+        let solution = find(6).unwrap();
+        let report = solution.run("nope");
+        assert!(report.has_errors());
+        let summary = report.summarise(solution);
+        assert!(summary.parse.error.is_some());
+        assert_eq!(None, summary.part_1);
+        assert_eq!(None, summary.part_2);
+    }
+
+    #[test]
+    fn solve_test() {
+        let r = find(6).unwrap().solve("3,4,3,1,2").unwrap();
+        assert_eq!(
+            Answers {
+                part_1: "5934".to_string(),
+                part_2: "26984457539".to_string()
+            },
+            r
+        );
+    }
+}
@@ -0,0 +1,234 @@
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::solution::{Registered, Report, Summary, Timed};
+
+/// Expected answers for the embedded puzzle inputs
+pub const EXPECTED_ANSWERS: &str = include_str!("../data/expected_answers.json");
+
+/// Day to expected answers, e.g. `{ "1": { "part_1": 1292, "part_2": "1262" } }`.
+///
+/// Answers can be numbers or strings, and either part can be left out to skip checking it.
+#[derive(Debug, Eq, PartialEq, Deserialize)]
+pub struct ExpectedAnswers(BTreeMap<usize, ExpectedParts>);
+
+#[derive(Debug, Eq, PartialEq, Default, Deserialize)]
+struct ExpectedParts {
+    part_1: Option<ExpectedAnswer>,
+    part_2: Option<ExpectedAnswer>,
+}
+
+#[derive(Debug, Eq, PartialEq, Deserialize)]
+#[serde(untagged)]
+enum ExpectedAnswer {
+    Number(serde_json::Number),
+    Text(String),
+}
+
+impl ExpectedAnswer {
+    fn matches(&self, actual: &str) -> bool {
+        match self {
+            ExpectedAnswer::Number(n) => n.to_string() == actual.trim(),
+            ExpectedAnswer::Text(s) => s.trim() == actual.trim(),
+        }
+    }
+}
+
+impl Display for ExpectedAnswer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExpectedAnswer::Number(n) => write!(f, "{}", n),
+            ExpectedAnswer::Text(s) => write!(f, "{}", s),
+        }
+    }
+}
+
+#[derive(Debug, Eq, PartialEq, Serialize)]
+pub struct Check {
+    pub day: usize,
+    pub part: usize,
+    pub verdict: Verdict,
+}
+
+#[derive(Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Verdict {
+    Match,
+    Mismatch { expected: String, actual: String },
+    Failed(String),
+    NoExpectation,
+}
+
+/// A day's [Summary] along with how its answers checked out, for `--verify` with `--format json`
+#[derive(Debug, Eq, PartialEq, Serialize)]
+pub struct VerifiedSummary {
+    #[serde(flatten)]
+    pub summary: Summary,
+    pub checks: Vec<Check>,
+}
+
+impl Verdict {
+    pub fn is_failure(&self) -> bool {
+        matches!(self, Verdict::Mismatch { .. } | Verdict::Failed(_))
+    }
+}
+
+impl Display for Check {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Day {} part {}: ", self.day, self.part)?;
+        match &self.verdict {
+            Verdict::Match => write!(f, "ok"),
+            Verdict::Mismatch { expected, actual } => {
+                write!(f, "MISMATCH expected [{}] got [{}]", expected, actual)
+            }
+            Verdict::Failed(e) => write!(f, "FAILED {}", e),
+            Verdict::NoExpectation => write!(f, "skipped (no expected answer)"),
+        }
+    }
+}
+
+impl ExpectedAnswers {
+    pub fn parse(s: &str) -> Result<ExpectedAnswers> {
+        serde_json::from_str(s).context("Failed to parse expected answers")
+    }
+
+    pub fn check(&self, solution: &Registered, report: &Report) -> Vec<Check> {
+        let no_expectations = ExpectedParts::default();
+        let expected = self.0.get(&solution.day).unwrap_or(&no_expectations);
+        [
+            (1, &expected.part_1, &report.part_1),
+            (2, &expected.part_2, &report.part_2),
+        ]
+        .iter()
+        .map(|(part, expected, actual)| Check {
+            day: solution.day,
+            part: *part,
+            verdict: verdict(expected.as_ref(), actual.as_ref(), report),
+        })
+        .collect()
+    }
+}
+
+fn verdict(
+    expected: Option<&ExpectedAnswer>,
+    actual: Option<&Timed<Result<String>>>,
+    report: &Report,
+) -> Verdict {
+    match (expected, actual) {
+        (None, _) => Verdict::NoExpectation,
+        (Some(_), None) => match &report.parse.value {
+            Err(e) => Verdict::Failed(format!("parse error: {:#}", e)),
+            Ok(_) => Verdict::Failed("not run".to_string()),
+        },
+        (Some(_), Some(Timed { value: Err(e), .. })) => Verdict::Failed(format!("{:#}", e)),
+        (
+            Some(expected),
+            Some(Timed {
+                value: Ok(actual), ..
+            }),
+        ) => {
+            if expected.matches(actual) {
+                Verdict::Match
+            } else {
+                Verdict::Mismatch {
+                    expected: expected.to_string(),
+                    actual: actual.clone(),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solution::{find, SOLUTIONS};
+
+    static TEST_EXPECTED: &str = r#"{
+  "6": { "part_1": 5934, "part_2": "26984457539" },
+  "7": { "part_1": 37 }
+}"#;
+
+    #[test]
+    fn parse_test() {
+        let r = ExpectedAnswers::parse(TEST_EXPECTED).unwrap();
+        assert_eq!(2, r.0.len());
+        assert_eq!(None, r.0[&7].part_2);
+        assert!(ExpectedAnswers::parse("{\"six\": {}}").is_err());
+    }
+
+    #[test]
+    fn check_match_test() {
+        let expected = ExpectedAnswers::parse(TEST_EXPECTED).unwrap();
+        let solution = find(6).unwrap();
+        let checks = expected.check(solution, &solution.run("3,4,3,1,2"));
+        assert_eq!(
+            vec![Verdict::Match, Verdict::Match],
+            checks.into_iter().map(|c| c.verdict).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn check_mismatch_test() {
+        let expected = ExpectedAnswers::parse(TEST_EXPECTED).unwrap();
+        let solution = find(7).unwrap();
+        let checks = expected.check(solution, &solution.run("1,2,3"));
+        assert_eq!(
+            vec![
+                Verdict::Mismatch {
+                    expected: "37".to_string(),
+                    actual: "2".to_string()
+                },
+                Verdict::NoExpectation
+            ],
+            checks.into_iter().map(|c| c.verdict).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn check_parse_failure_test() {
+        let expected = ExpectedAnswers::parse(TEST_EXPECTED).unwrap();
+        let solution = find(6).unwrap();
+        let checks = expected.check(solution, &solution.run("nope"));
+        assert!(checks.iter().all(|c| c.verdict.is_failure()));
+    }
+
+    #[test]
+    fn verified_summary_json_test() {
+        let expected = ExpectedAnswers::parse(TEST_EXPECTED).unwrap();
+        let solution = find(7).unwrap();
+        let report = solution.run("1,2,3");
+        let verified = VerifiedSummary {
+            summary: report.summarise(solution),
+            checks: expected.check(solution, &report),
+        };
+        let json: serde_json::Value = serde_json::to_value(&verified).unwrap();
+        assert_eq!(7, json["day"]);
+        assert_eq!("2", json["part_1"]["answer"]);
+        assert_eq!(
+            serde_json::json!([
+                {
+                    "day": 7,
+                    "part": 1,
+                    "verdict": { "mismatch": { "expected": "37", "actual": "2" } }
+                },
+                { "day": 7, "part": 2, "verdict": "no_expectation" }
+            ]),
+            json["checks"]
+        );
+    }
+
+    #[test]
+    fn embedded_expected_answers_test() {
+        let expected = ExpectedAnswers::parse(EXPECTED_ANSWERS).unwrap();
+        // Day 19 has its own slow test
+        for solution in SOLUTIONS.iter().filter(|s| s.day != 19) {
+            for check in expected.check(solution, &solution.run(solution.input)) {
+                assert_eq!(Verdict::Match, check.verdict, "{}", check);
+            }
+        }
+    }
+}